@@ -0,0 +1 @@
+DROP INDEX tracked_items_repo_id_foreign_id;
@@ -0,0 +1,5 @@
+-- Keep only the most recent copy of any item that was tracked more than once
+DELETE FROM tracked_items
+WHERE id NOT IN (SELECT MAX(id) FROM tracked_items GROUP BY repo_id, foreign_id);
+
+CREATE UNIQUE INDEX tracked_items_repo_id_foreign_id ON tracked_items (repo_id, foreign_id);
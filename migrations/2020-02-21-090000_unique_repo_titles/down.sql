@@ -0,0 +1 @@
+DROP INDEX repos_title;
@@ -0,0 +1,14 @@
+-- Fold any repo that was added more than once into its oldest copy
+UPDATE OR IGNORE tracked_items
+SET repo_id = (SELECT MIN(kept.id)
+               FROM repos kept
+               JOIN repos dup ON dup.title = kept.title
+               WHERE dup.id = tracked_items.repo_id);
+
+DELETE FROM tracked_items
+WHERE repo_id NOT IN (SELECT MIN(id) FROM repos GROUP BY title);
+
+DELETE FROM repos
+WHERE id NOT IN (SELECT MIN(id) FROM repos GROUP BY title);
+
+CREATE UNIQUE INDEX repos_title ON repos (title);
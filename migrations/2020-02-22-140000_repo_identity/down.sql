@@ -0,0 +1,11 @@
+CREATE TABLE repos_without_identity (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL ,
+  title TEXT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+INSERT INTO repos_without_identity (id, title, created_at, updated_at)
+SELECT id, title, created_at, updated_at FROM repos;
+DROP TABLE repos;
+ALTER TABLE repos_without_identity RENAME TO repos;
+CREATE UNIQUE INDEX repos_title ON repos (title);
@@ -0,0 +1,4 @@
+-- The GitHub node ID survives renames and transfers, unlike the title
+ALTER TABLE repos ADD COLUMN foreign_id TEXT;
+ALTER TABLE repos ADD COLUMN renamed_from TEXT;
+ALTER TABLE repos ADD COLUMN renamed_at TIMESTAMP;
@@ -0,0 +1,2 @@
+-- Only open items used to be tracked
+ALTER TABLE tracked_items ADD COLUMN state TEXT NOT NULL DEFAULT 'open';
@@ -0,0 +1,2 @@
+ALTER TABLE tracked_items ADD COLUMN closed_at TIMESTAMP;
+ALTER TABLE tracked_items ADD COLUMN closed_by TEXT;
@@ -0,0 +1,14 @@
+CREATE TABLE item_events
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    repo_id     INTEGER                           NOT NULL,
+    foreign_id  TEXT                              NOT NULL,
+    number      INTEGER                           NOT NULL,
+    change      TEXT                              NOT NULL,
+    old_value   TEXT,
+    new_value   TEXT,
+    recorded_at TIMESTAMP                         NOT NULL,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+
+CREATE INDEX item_events_repo_id_number ON item_events (repo_id, number);
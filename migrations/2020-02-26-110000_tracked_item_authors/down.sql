@@ -0,0 +1,25 @@
+CREATE TABLE tracked_items_without_authors
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    repo_id    INTEGER                           NOT NULL,
+    foreign_id TEXT                              NOT NULL,
+    number     INTEGER                           NOT NULL,
+    title      TEXT                              NOT NULL,
+    by         TEXT                              NOT NULL,
+    link       TEXT                              NOT NULL,
+    labels     TEXT                              NOT NULL,
+    kind       TEXT                              NOT NULL,
+    last_updated TIMESTAMP                       NOT NULL,
+    created_at TIMESTAMP                         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP                         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    state      TEXT                              NOT NULL DEFAULT 'open',
+    closed_at  TIMESTAMP,
+    closed_by  TEXT,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+INSERT INTO tracked_items_without_authors
+SELECT id, repo_id, foreign_id, number, title, by, link, labels, kind, last_updated, created_at, updated_at, state, closed_at, closed_by
+FROM tracked_items;
+DROP TABLE tracked_items;
+ALTER TABLE tracked_items_without_authors RENAME TO tracked_items;
+CREATE UNIQUE INDEX tracked_items_repo_id_foreign_id ON tracked_items (repo_id, foreign_id);
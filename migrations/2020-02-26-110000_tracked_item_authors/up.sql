@@ -0,0 +1,3 @@
+ALTER TABLE tracked_items ADD COLUMN by_url TEXT;
+ALTER TABLE tracked_items ADD COLUMN by_avatar TEXT;
+ALTER TABLE tracked_items ADD COLUMN by_kind TEXT NOT NULL DEFAULT 'user';
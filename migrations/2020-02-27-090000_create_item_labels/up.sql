@@ -0,0 +1,54 @@
+CREATE TABLE item_labels
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    item_id     INTEGER                           NOT NULL,
+    name        TEXT                              NOT NULL,
+    color       TEXT,
+    description TEXT,
+    FOREIGN KEY (item_id) REFERENCES tracked_items (id)
+);
+
+-- Labels used to be joined with commas, so that is the best we can do to tell them apart
+WITH RECURSIVE split(item_id, name, rest) AS (
+    SELECT id, '', labels || ','
+    FROM tracked_items
+    WHERE labels != ''
+    UNION ALL
+    SELECT item_id, substr(rest, 1, instr(rest, ',') - 1), substr(rest, instr(rest, ',') + 1)
+    FROM split
+    WHERE rest != ''
+)
+INSERT INTO item_labels (item_id, name)
+SELECT item_id, name
+FROM split
+WHERE name != '';
+
+CREATE INDEX item_labels_item_id ON item_labels (item_id);
+
+CREATE TABLE tracked_items_without_labels
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    repo_id    INTEGER                           NOT NULL,
+    foreign_id TEXT                              NOT NULL,
+    number     INTEGER                           NOT NULL,
+    title      TEXT                              NOT NULL,
+    by         TEXT                              NOT NULL,
+    link       TEXT                              NOT NULL,
+    kind       TEXT                              NOT NULL,
+    last_updated TIMESTAMP                       NOT NULL,
+    created_at TIMESTAMP                         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP                         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    state      TEXT                              NOT NULL DEFAULT 'open',
+    closed_at  TIMESTAMP,
+    closed_by  TEXT,
+    by_url     TEXT,
+    by_avatar  TEXT,
+    by_kind    TEXT                              NOT NULL DEFAULT 'user',
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+INSERT INTO tracked_items_without_labels
+SELECT id, repo_id, foreign_id, number, title, by, link, kind, last_updated, created_at, updated_at, state, closed_at, closed_by, by_url, by_avatar, by_kind
+FROM tracked_items;
+DROP TABLE tracked_items;
+ALTER TABLE tracked_items_without_labels RENAME TO tracked_items;
+CREATE UNIQUE INDEX tracked_items_repo_id_foreign_id ON tracked_items (repo_id, foreign_id);
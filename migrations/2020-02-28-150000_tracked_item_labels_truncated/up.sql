@@ -0,0 +1 @@
+ALTER TABLE tracked_items ADD COLUMN labels_truncated BOOLEAN NOT NULL DEFAULT 0;
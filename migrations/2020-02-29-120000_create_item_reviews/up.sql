@@ -0,0 +1,14 @@
+ALTER TABLE tracked_items ADD COLUMN review_decision TEXT;
+
+-- Outstanding review requests are kept with the state 'requested'
+CREATE TABLE item_reviews
+(
+    id           INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    item_id      INTEGER                           NOT NULL,
+    reviewer     TEXT                              NOT NULL,
+    state        TEXT                              NOT NULL,
+    submitted_at TIMESTAMP,
+    FOREIGN KEY (item_id) REFERENCES tracked_items (id)
+);
+
+CREATE INDEX item_reviews_item_id ON item_reviews (item_id);
@@ -0,0 +1,31 @@
+DROP TABLE item_failing_checks;
+
+CREATE TABLE tracked_items_without_ci_status
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    repo_id    INTEGER                           NOT NULL,
+    foreign_id TEXT                              NOT NULL,
+    number     INTEGER                           NOT NULL,
+    title      TEXT                              NOT NULL,
+    by         TEXT                              NOT NULL,
+    link       TEXT                              NOT NULL,
+    kind       TEXT                              NOT NULL,
+    last_updated TIMESTAMP                       NOT NULL,
+    created_at TIMESTAMP                         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP                         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    state      TEXT                              NOT NULL DEFAULT 'open',
+    closed_at  TIMESTAMP,
+    closed_by  TEXT,
+    by_url     TEXT,
+    by_avatar  TEXT,
+    by_kind    TEXT                              NOT NULL DEFAULT 'user',
+    labels_truncated BOOLEAN                     NOT NULL DEFAULT 0,
+    review_decision TEXT,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+INSERT INTO tracked_items_without_ci_status
+SELECT id, repo_id, foreign_id, number, title, by, link, kind, last_updated, created_at, updated_at, state, closed_at, closed_by, by_url, by_avatar, by_kind, labels_truncated, review_decision
+FROM tracked_items;
+DROP TABLE tracked_items;
+ALTER TABLE tracked_items_without_ci_status RENAME TO tracked_items;
+CREATE UNIQUE INDEX tracked_items_repo_id_foreign_id ON tracked_items (repo_id, foreign_id);
@@ -0,0 +1,12 @@
+ALTER TABLE tracked_items ADD COLUMN ci_status TEXT;
+
+-- Only the checks that failed on the head commit of a PR are kept
+CREATE TABLE item_failing_checks
+(
+    id      INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    item_id INTEGER                           NOT NULL,
+    name    TEXT                              NOT NULL,
+    FOREIGN KEY (item_id) REFERENCES tracked_items (id)
+);
+
+CREATE INDEX item_failing_checks_item_id ON item_failing_checks (item_id);
@@ -0,0 +1,12 @@
+CREATE TABLE item_timeline
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    item_id     INTEGER                           NOT NULL,
+    kind        TEXT                              NOT NULL,
+    actor       TEXT                              NOT NULL,
+    happened_at TIMESTAMP                         NOT NULL,
+    detail      TEXT,
+    FOREIGN KEY (item_id) REFERENCES tracked_items (id)
+);
+
+CREATE INDEX item_timeline_item_id ON item_timeline (item_id);
@@ -0,0 +1 @@
+DROP TABLE last_commits;
@@ -0,0 +1,11 @@
+-- The latest commit on the default branch, one per repo
+CREATE TABLE last_commits
+(
+    repo_id      INTEGER PRIMARY KEY NOT NULL,
+    branch       TEXT                NOT NULL,
+    sha          TEXT                NOT NULL,
+    headline     TEXT                NOT NULL,
+    author       TEXT                NOT NULL,
+    committed_at TIMESTAMP           NOT NULL,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
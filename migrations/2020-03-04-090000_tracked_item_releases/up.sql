@@ -0,0 +1,3 @@
+ALTER TABLE tracked_items ADD COLUMN release_tag TEXT;
+ALTER TABLE tracked_items ADD COLUMN release_prerelease BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE tracked_items ADD COLUMN release_published_at TIMESTAMP;
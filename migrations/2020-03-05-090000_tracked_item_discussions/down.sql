@@ -0,0 +1,38 @@
+CREATE TABLE tracked_items_without_discussions
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    repo_id    INTEGER                           NOT NULL,
+    foreign_id TEXT                              NOT NULL,
+    number     INTEGER                           NOT NULL,
+    title      TEXT                              NOT NULL,
+    by         TEXT                              NOT NULL,
+    link       TEXT                              NOT NULL,
+    kind       TEXT                              NOT NULL,
+    last_updated TIMESTAMP                       NOT NULL,
+    created_at TIMESTAMP                         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP                         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    state      TEXT                              NOT NULL DEFAULT 'open',
+    closed_at  TIMESTAMP,
+    closed_by  TEXT,
+    by_url     TEXT,
+    by_avatar  TEXT,
+    by_kind    TEXT                              NOT NULL DEFAULT 'user',
+    labels_truncated BOOLEAN                     NOT NULL DEFAULT 0,
+    review_decision TEXT,
+    ci_status  TEXT,
+    release_tag TEXT,
+    release_prerelease BOOLEAN                   NOT NULL DEFAULT 0,
+    release_published_at TIMESTAMP,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+DELETE FROM item_labels WHERE item_id IN (SELECT id FROM tracked_items WHERE kind = 'discussion');
+DELETE FROM item_reviews WHERE item_id IN (SELECT id FROM tracked_items WHERE kind = 'discussion');
+DELETE FROM item_failing_checks WHERE item_id IN (SELECT id FROM tracked_items WHERE kind = 'discussion');
+DELETE FROM item_timeline WHERE item_id IN (SELECT id FROM tracked_items WHERE kind = 'discussion');
+INSERT INTO tracked_items_without_discussions
+SELECT id, repo_id, foreign_id, number, title, by, link, kind, last_updated, created_at, updated_at, state, closed_at, closed_by, by_url, by_avatar, by_kind, labels_truncated, review_decision, ci_status, release_tag, release_prerelease, release_published_at
+FROM tracked_items
+WHERE kind <> 'discussion';
+DROP TABLE tracked_items;
+ALTER TABLE tracked_items_without_discussions RENAME TO tracked_items;
+CREATE UNIQUE INDEX tracked_items_repo_id_foreign_id ON tracked_items (repo_id, foreign_id);
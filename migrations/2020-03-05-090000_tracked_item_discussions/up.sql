@@ -0,0 +1,3 @@
+ALTER TABLE tracked_items ADD COLUMN discussion_category TEXT;
+ALTER TABLE tracked_items ADD COLUMN discussion_answered BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE tracked_items ADD COLUMN discussion_comments INTEGER;
@@ -0,0 +1,17 @@
+DROP TABLE branch_health;
+
+CREATE TABLE last_commits_without_ci_status
+(
+    repo_id      INTEGER PRIMARY KEY NOT NULL,
+    branch       TEXT                NOT NULL,
+    sha          TEXT                NOT NULL,
+    headline     TEXT                NOT NULL,
+    author       TEXT                NOT NULL,
+    committed_at TIMESTAMP           NOT NULL,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+INSERT INTO last_commits_without_ci_status
+SELECT repo_id, branch, sha, headline, author, committed_at
+FROM last_commits;
+DROP TABLE last_commits;
+ALTER TABLE last_commits_without_ci_status RENAME TO last_commits;
@@ -0,0 +1,10 @@
+ALTER TABLE last_commits ADD COLUMN ci_status TEXT;
+
+-- Whether the default branch is green, and since when, one per repo
+CREATE TABLE branch_health
+(
+    repo_id    INTEGER PRIMARY KEY NOT NULL,
+    status     TEXT                NOT NULL,
+    changed_at TIMESTAMP           NOT NULL,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
@@ -0,0 +1,4 @@
+UPDATE item_events
+SET change    = old_value,
+    old_value = NULL
+WHERE change = 'reviewed';
@@ -0,0 +1,6 @@
+-- Reviews used to be stored under their state, which left no room for other kinds of change
+UPDATE item_events
+SET old_value = change,
+    change    = 'reviewed'
+WHERE change IN ('approved', 'changes_requested', 'commented', 'dismissed', 'pending')
+  AND old_value IS NULL;
@@ -0,0 +1,14 @@
+DROP TABLE repo_events;
+
+CREATE TABLE branch_health_since_known
+(
+    repo_id    INTEGER PRIMARY KEY NOT NULL,
+    status     TEXT                NOT NULL,
+    changed_at TIMESTAMP           NOT NULL,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+INSERT INTO branch_health_since_known
+SELECT repo_id, status, COALESCE(changed_at, CURRENT_TIMESTAMP)
+FROM branch_health;
+DROP TABLE branch_health;
+ALTER TABLE branch_health_since_known RENAME TO branch_health;
@@ -0,0 +1,27 @@
+-- Whether the default branch is green, and since when if the updater saw it turn
+CREATE TABLE branch_health_since_unknown
+(
+    repo_id    INTEGER PRIMARY KEY NOT NULL,
+    status     TEXT                NOT NULL,
+    changed_at TIMESTAMP,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+INSERT INTO branch_health_since_unknown
+SELECT repo_id, status, changed_at
+FROM branch_health;
+DROP TABLE branch_health;
+ALTER TABLE branch_health_since_unknown RENAME TO branch_health;
+
+-- Changes to a repo as a whole, like its default branch turning red
+CREATE TABLE repo_events
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    repo_id     INTEGER                           NOT NULL,
+    change      TEXT                              NOT NULL,
+    old_value   TEXT,
+    new_value   TEXT,
+    recorded_at TIMESTAMP                         NOT NULL,
+    FOREIGN KEY (repo_id) REFERENCES repos (id)
+);
+
+CREATE INDEX repo_events_repo_id ON repo_events (repo_id);
@@ -0,0 +1 @@
+DROP INDEX item_events_repo_id_foreign_id;
@@ -0,0 +1,2 @@
+-- Histories are loaded by the node ID of their item
+CREATE INDEX item_events_repo_id_foreign_id ON item_events (repo_id, foreign_id);
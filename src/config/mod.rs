@@ -4,6 +4,8 @@ use serde::de::{self, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer};
 
 use crate::db::{self, Db};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 pub fn from_str(content: &str) -> Result<Config> {
@@ -99,16 +101,56 @@ impl ServerConfig {
 #[derive(Deserialize, Clone, Debug)]
 pub struct GithubConfig {
     pub token: String,
+    /// Base of the API, e.g. `https://github.example.com/api` for GitHub Enterprise Server.
+    pub api_url: Option<String>,
+    /// Extra headers sent along with every request.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Upper bound for PRs, for issues and for discussions fetched when browsing a repo.
+    pub max_items: Option<usize>,
+    /// Upper bound for labels fetched per issue or PR, at most 100.
+    pub max_labels: Option<usize>,
+    /// How many of the latest comments, closes and cross-references are kept per item, at most 100.
+    pub timeline_events: Option<usize>,
+    /// How many of the latest releases are kept per repo, at most 100.
+    pub max_releases: Option<usize>,
+    /// How often a request is retried when GitHub fails transiently.
+    pub max_retries: Option<u32>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct UpdaterConfig {
     pub run: bool,
+    /// Rate limit points the updater leaves for browsing repos.
+    pub rate_limit_reserve: Option<i64>,
+    /// How many hours closed and merged items stay on the dashboard.
+    #[serde(default, deserialize_with = "hours")]
+    pub keep_resolved_hours: Option<u32>,
+}
+
+fn hours<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<i64>::deserialize(deserializer)? {
+        Some(hours) => u32::try_from(hours).map(Some).map_err(|_| {
+            de::Error::custom(format!(
+                "keep_resolved_hours must be between 0 and {}, not {}",
+                u32::MAX,
+                hours
+            ))
+        }),
+        None => Ok(None),
+    }
 }
 
 impl std::default::Default for UpdaterConfig {
     fn default() -> UpdaterConfig {
-        UpdaterConfig { run: true }
+        UpdaterConfig {
+            run: true,
+            rate_limit_reserve: None,
+            keep_resolved_hours: None,
+        }
     }
 }
 
@@ -201,4 +243,92 @@ mod tests {
 
         assert_eq!(config.database.backing, Backing::Sqlite)
     }
+
+    #[test]
+    fn it_can_point_the_github_client_at_an_enterprise_server() {
+        let sample_config = r#"
+{
+  "database": {
+    "backing": "sqlite",
+    "file": "./repos.db",
+    "run_migrations": true
+  },
+  "server": {
+    "port": 8080
+  },
+  "github": {
+    "token": "some-token",
+    "api_url": "https://github.example.com/api",
+    "headers": {
+      "X-Some-Proxy": "value"
+    }
+  },
+  "updater": {
+      "run": true
+    }
+}
+"#;
+
+        let config = serde_json::from_str::<Config>(sample_config).unwrap();
+
+        assert_eq!(
+            config.github.api_url,
+            Some("https://github.example.com/api".to_string())
+        );
+        assert_eq!(config.github.headers["X-Some-Proxy"], "value");
+    }
+
+    #[test]
+    fn it_can_keep_resolved_items_around_for_a_while() {
+        let sample_config = r#"
+{
+  "database": {
+    "backing": "sqlite",
+    "file": "./repos.db",
+    "run_migrations": true
+  },
+  "server": {
+    "port": 8080
+  },
+  "github": {
+    "token": "some-token"
+  },
+  "updater": {
+      "run": true,
+      "keep_resolved_hours": 72
+    }
+}
+"#;
+
+        let config = serde_json::from_str::<Config>(sample_config).unwrap();
+
+        assert_eq!(config.updater.keep_resolved_hours, Some(72));
+    }
+
+    #[test]
+    fn it_refuses_to_keep_resolved_items_for_negative_hours() {
+        let sample_config = r#"
+{
+  "database": {
+    "backing": "sqlite",
+    "file": "./repos.db",
+    "run_migrations": true
+  },
+  "server": {
+    "port": 8080
+  },
+  "github": {
+    "token": "some-token"
+  },
+  "updater": {
+      "run": true,
+      "keep_resolved_hours": -1
+    }
+}
+"#;
+
+        let error = from_str(sample_config).unwrap_err();
+
+        assert!(format!("{:#}", error).contains("keep_resolved_hours"));
+    }
 }
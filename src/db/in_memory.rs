@@ -3,13 +3,21 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 
 use anyhow::{bail, Result};
+use chrono::Utc;
 
 use crate::db::{Db, FullStoredRepo, StoredRepo};
-use crate::domain::{ItemKind, NewTrackedItem};
+use crate::domain::{
+    BranchHealth, Commit, ItemEvent, ItemKind, NewTrackedItem, RemoteRepo, RepoAlreadyExists,
+    RepoEvent,
+};
 
 struct RepoAndItems {
     repo: StoredRepo,
     items: Vec<NewTrackedItem>,
+    events: Vec<ItemEvent>,
+    last_commit: Option<Commit>,
+    branch_health: Option<BranchHealth>,
+    repo_events: Vec<RepoEvent>,
 }
 
 struct InMemory {
@@ -24,6 +32,35 @@ pub fn new() -> impl Db {
     }
 }
 
+impl InMemory {
+    fn insert_repo(&self, repo_name: &str, foreign_id: Option<&str>) -> Result<StoredRepo> {
+        if self.find_repo(repo_name).is_some() {
+            return Err(RepoAlreadyExists(repo_name.to_string()).into());
+        }
+
+        let mut id = self.id.lock().unwrap();
+
+        let next = *id + 1;
+        *id = next;
+        let mut repo = StoredRepo::new(next, repo_name);
+        repo.foreign_id = foreign_id.map(String::from);
+
+        self.repos.lock().unwrap().get_mut().insert(
+            repo.title.clone(),
+            RepoAndItems {
+                repo: repo.clone(),
+                items: Vec::new(),
+                events: Vec::new(),
+                last_commit: None,
+                branch_health: None,
+                repo_events: Vec::new(),
+            },
+        );
+
+        Ok(repo)
+    }
+}
+
 impl Db for InMemory {
     fn find_repo(&self, repo_name: &str) -> Option<StoredRepo> {
         self.repos
@@ -42,59 +79,60 @@ impl Db for InMemory {
             .get_mut()
             // Hashmap from here downwards
             .entry(repo.title.clone())
-            .and_modify(|t| t.items.append(&mut items.clone()));
+            .and_modify(|t| {
+                for item in items {
+                    match t.items.iter_mut().find(|i| i.foreign_id == item.foreign_id) {
+                        Some(existing) => *existing = item,
+                        None => t.items.push(item),
+                    }
+                }
+            });
 
         Ok(())
     }
 
-    fn update_tracked_item(&self, _repo: &StoredRepo, item: NewTrackedItem) -> Result<()> {
-        for (idx, v) in self
-            .repos
-            .lock()
-            .unwrap()
-            .get_mut()
-            // Hashmap from here downwards
-            .values_mut()
-            .enumerate()
-        {
-            let found = v.items.iter().find(|i| i.foreign_id == item.foreign_id);
-
-            if found.is_some() {
-                v.items.remove(idx);
-                v.items.push(item);
+    fn update_tracked_item(&self, repo: &StoredRepo, item: NewTrackedItem) -> Result<()> {
+        let mut repos = self.repos.lock().unwrap();
+        let found = repos.get_mut().get_mut(&repo.title).and_then(|stored| {
+            stored
+                .items
+                .iter_mut()
+                .find(|i| i.foreign_id == item.foreign_id)
+        });
 
-                return Ok(());
+        match found {
+            Some(existing) => {
+                *existing = item;
+                Ok(())
             }
+            None => bail!(
+                "original with foreign id {} not found when updating",
+                item.foreign_id
+            ),
         }
-
-        bail!(
-            "original with foreign id {} not found when updating",
-            item.foreign_id
-        )
     }
 
-    fn remove_tracked_item(&self, _repo: &StoredRepo, item: NewTrackedItem) -> Result<()> {
-        for (idx, v) in self
-            .repos
-            .lock()
-            .unwrap()
-            .get_mut()
-            // Hashmap from here downwards
-            .values_mut()
-            .enumerate()
-        {
-            let found = v.items.iter().find(|i| i.foreign_id == item.foreign_id);
+    fn remove_tracked_item(&self, repo: &StoredRepo, item: NewTrackedItem) -> Result<()> {
+        let mut repos = self.repos.lock().unwrap();
+        let stored = match repos.get_mut().get_mut(&repo.title) {
+            Some(stored) => stored,
+            None => bail!("{} not found", repo.title),
+        };
 
-            if found.is_some() {
-                v.items.remove(idx);
-                return Ok(());
+        match stored
+            .items
+            .iter()
+            .position(|i| i.foreign_id == item.foreign_id)
+        {
+            Some(idx) => {
+                stored.items.remove(idx);
+                Ok(())
             }
+            None => bail!(
+                "original with foreign id {} not found when removing",
+                item.foreign_id
+            ),
         }
-
-        bail!(
-            "original with foreign id {} not found when removing",
-            item.foreign_id
-        )
     }
 
     fn all(&self) -> Result<Vec<FullStoredRepo>> {
@@ -107,17 +145,28 @@ impl Db for InMemory {
             // Hashmap from here downwards
             .values()
         {
-            let (issues, prs) = thing
+            let (releases, items): (Vec<_>, Vec<_>) = thing
                 .items
                 .clone()
                 .into_iter()
-                .partition(|i| i.kind == ItemKind::Issue);
+                .partition(|i| i.kind() == ItemKind::Release);
+            let (discussions, items): (Vec<_>, Vec<_>) = items
+                .into_iter()
+                .partition(|i| i.kind() == ItemKind::Discussion);
+            let (issues, prs) = items.into_iter().partition(|i| i.kind() == ItemKind::Issue);
 
             let r = FullStoredRepo {
                 id: thing.repo.id,
                 title: thing.repo.title.clone(),
+                foreign_id: thing.repo.foreign_id.clone(),
+                renamed_from: thing.repo.renamed_from.clone(),
+                renamed_at: thing.repo.renamed_at,
+                last_commit: thing.last_commit.clone(),
+                branch_health: thing.branch_health.clone(),
                 issues,
                 prs,
+                releases,
+                discussions,
             };
             result.push(r)
         }
@@ -125,21 +174,125 @@ impl Db for InMemory {
     }
 
     fn insert_new_repo(&self, repo_name: &str) -> Result<StoredRepo> {
-        let mut id = self.id.lock().unwrap();
+        self.insert_repo(repo_name, None)
+    }
 
-        let next = *id + 1;
-        *id = next;
-        let repo = StoredRepo::new(next, repo_name);
+    fn insert_remote_repo(&self, remote: &RemoteRepo) -> Result<StoredRepo> {
+        self.insert_repo(&remote.name.to_string(), Some(&remote.foreign_id))
+    }
 
-        self.repos.lock().unwrap().get_mut().insert(
-            repo.title.clone(),
-            RepoAndItems {
-                repo: repo.clone(),
-                items: Vec::new(),
-            },
-        );
+    fn set_repo_foreign_id(&self, repo: &StoredRepo, foreign_id: &str) -> Result<()> {
+        match self.repos.lock().unwrap().get_mut().get_mut(&repo.title) {
+            Some(stored) => {
+                stored.repo.foreign_id = Some(foreign_id.to_string());
+                Ok(())
+            }
+            None => bail!("{} not found", repo.title),
+        }
+    }
 
-        Ok(repo)
+    fn rename_repo(&self, repo: &StoredRepo, new_title: &str) -> Result<StoredRepo> {
+        let mut locked = self.repos.lock().unwrap();
+        let repos = locked.get_mut();
+
+        if repos.contains_key(new_title) {
+            return Err(RepoAlreadyExists(new_title.to_string()).into());
+        }
+
+        let mut stored = match repos.remove(&repo.title) {
+            Some(stored) => stored,
+            None => bail!("{} not found", repo.title),
+        };
+
+        stored.repo.renamed_from = Some(stored.repo.title.clone());
+        stored.repo.renamed_at = Some(Utc::now().naive_utc());
+        stored.repo.title = new_title.to_string();
+        let renamed = stored.repo.clone();
+        repos.insert(new_title.to_string(), stored);
+
+        Ok(renamed)
+    }
+
+    fn record_events(&self, repo: &StoredRepo, mut events: Vec<ItemEvent>) -> Result<()> {
+        match self.repos.lock().unwrap().get_mut().get_mut(&repo.title) {
+            Some(stored) => {
+                stored.events.append(&mut events);
+                Ok(())
+            }
+            None => bail!("{} not found", repo.title),
+        }
+    }
+
+    fn history(&self, repo: &StoredRepo, foreign_id: &str) -> Result<Vec<ItemEvent>> {
+        match self.repos.lock().unwrap().get_mut().get(&repo.title) {
+            Some(stored) => Ok(stored
+                .events
+                .iter()
+                .filter(|event| event.foreign_id == foreign_id)
+                .cloned()
+                .collect()),
+            None => bail!("{} not found", repo.title),
+        }
+    }
+
+    fn item_foreign_id(&self, repo: &StoredRepo, number: i32) -> Result<Option<String>> {
+        let mut repos = self.repos.lock().unwrap();
+        let stored = match repos.get_mut().get(&repo.title) {
+            Some(stored) => stored,
+            None => bail!("{} not found", repo.title),
+        };
+
+        let tracked = stored
+            .items
+            .iter()
+            .find(|item| item.number == number && item.kind() != ItemKind::Release)
+            .map(|item| item.foreign_id.clone());
+
+        Ok(tracked.or_else(|| {
+            stored
+                .events
+                .iter()
+                .rev()
+                .find(|event| event.number == number && !event.change.is_release())
+                .map(|event| event.foreign_id.clone())
+        }))
+    }
+
+    fn set_last_commit(&self, repo: &StoredRepo, commit: Commit) -> Result<()> {
+        match self.repos.lock().unwrap().get_mut().get_mut(&repo.title) {
+            Some(stored) => {
+                stored.last_commit = Some(commit);
+                Ok(())
+            }
+            None => bail!("{} not found", repo.title),
+        }
+    }
+
+    fn set_branch_health(&self, repo: &StoredRepo, health: BranchHealth) -> Result<()> {
+        match self.repos.lock().unwrap().get_mut().get_mut(&repo.title) {
+            Some(stored) => {
+                stored.branch_health = Some(health);
+                Ok(())
+            }
+            None => bail!("{} not found", repo.title),
+        }
+    }
+
+    fn record_repo_events(&self, repo: &StoredRepo, mut events: Vec<RepoEvent>) -> Result<()> {
+        match self.repos.lock().unwrap().get_mut().get_mut(&repo.title) {
+            Some(stored) => {
+                stored.repo_events.append(&mut events);
+                Ok(())
+            }
+            None => bail!("{} not found", repo.title),
+        }
+    }
+
+    fn repo_history(&self, repo: &StoredRepo) -> Result<Vec<RepoEvent>> {
+        match self.repos.lock().unwrap().get_mut().get(&repo.title) {
+            Some(stored) => Ok(stored.repo_events.clone()),
+            None => bail!("{} not found", repo.title),
+        }
     }
 
     fn delete(&self, repo: StoredRepo) -> Result<()> {
@@ -1,14 +1,19 @@
 use std::cell::RefCell;
+use std::convert::TryFrom;
 use std::path::Path;
 use std::sync::Mutex;
 
 use anyhow::{Context, Error};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::db::{Db, FullStoredRepo, StoredRepo};
-use crate::domain::api::Item;
-use crate::domain::{Author, ItemKind, Label, NewTrackedItem, State};
+use crate::domain::api::{self, Item};
+use crate::domain::{
+    Author, BranchHealth, Change, Checks, Commit, Discussion, ItemDetails, ItemEvent, ItemKind,
+    Label, NewTrackedItem, Release, RemoteRepo, RepoAlreadyExists, RepoChange, RepoEvent,
+    ReviewState, Reviews, State, TimelineEvent,
+};
 
 pub struct JsonStore {
     backing_store: jfs::Store,
@@ -30,20 +35,157 @@ pub fn new<P: AsRef<Path>>(path: P) -> impl Db {
 struct Repo {
     id: i32,
     title: String,
-    items: Vec<Item>,
+    items: Vec<StoredItem>,
+    #[serde(default)]
+    foreign_id: Option<String>,
+    #[serde(default)]
+    renamed_from: Option<String>,
+    #[serde(default)]
+    renamed_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    history: Vec<StoredEvent>,
+    #[serde(default)]
+    last_commit: Option<api::Commit>,
+    #[serde(default)]
+    branch_health: Option<api::BranchHealth>,
+    #[serde(default)]
+    repo_history: Vec<StoredRepoEvent>,
+}
+
+impl Repo {
+    fn stored(&self) -> StoredRepo {
+        StoredRepo {
+            id: self.id,
+            title: self.title.clone(),
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+            foreign_id: self.foreign_id.clone(),
+            renamed_from: self.renamed_from.clone(),
+            renamed_at: self.renamed_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct StoredItem {
+    /// Older files did not keep the GitHub node ID around.
+    #[serde(default)]
+    foreign_id: String,
+    #[serde(flatten)]
+    item: Item,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct StoredEvent {
+    foreign_id: String,
+    number: i32,
+    change: String,
+    from: Option<String>,
+    to: Option<String>,
+    recorded_at: DateTime<Utc>,
+}
+
+impl StoredEvent {
+    fn change(&self) -> Result<Change, Error> {
+        // Older files stored reviews under their state
+        if self.from.is_none() && self.change.parse::<ReviewState>().is_ok() {
+            return Change::from_parts("reviewed", Some(self.change.clone()), self.to.clone());
+        }
+
+        Change::from_parts(&self.change, self.from.clone(), self.to.clone())
+    }
+}
+
+impl From<ItemEvent> for StoredEvent {
+    fn from(other: ItemEvent) -> Self {
+        let (change, from, to) = other.change.to_parts();
+
+        StoredEvent {
+            foreign_id: other.foreign_id,
+            number: other.number,
+            change: change.to_string(),
+            from,
+            to,
+            recorded_at: other.recorded_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct StoredRepoEvent {
+    change: String,
+    from: Option<String>,
+    to: Option<String>,
+    recorded_at: DateTime<Utc>,
+}
+
+impl From<RepoEvent> for StoredRepoEvent {
+    fn from(other: RepoEvent) -> Self {
+        let (change, from, to) = other.change.to_parts();
+
+        StoredRepoEvent {
+            change: change.to_string(),
+            from,
+            to,
+            recorded_at: other.recorded_at,
+        }
+    }
+}
+
+impl TryFrom<StoredRepoEvent> for RepoEvent {
+    type Error = Error;
+
+    fn try_from(other: StoredRepoEvent) -> Result<Self, Error> {
+        Ok(RepoEvent {
+            change: RepoChange::from_parts(&other.change, other.from, other.to)?,
+            recorded_at: other.recorded_at,
+        })
+    }
+}
+
+impl From<NewTrackedItem> for StoredItem {
+    fn from(other: NewTrackedItem) -> Self {
+        StoredItem {
+            foreign_id: other.foreign_id.clone(),
+            item: Item::from(other),
+        }
+    }
+}
+
+impl JsonStore {
+    fn insert_repo(&self, repo_name: &str, foreign_id: Option<&str>) -> Result<StoredRepo, Error> {
+        if self.find_repo(repo_name).is_some() {
+            return Err(RepoAlreadyExists(repo_name.to_string()).into());
+        }
+
+        let mut locked = self.next_id.lock().unwrap();
+        let next = locked.get_mut();
+        let id = *next + 1;
+        *next = id;
+
+        let repo = Repo {
+            id,
+            title: repo_name.to_owned(),
+            items: Vec::new(),
+            foreign_id: foreign_id.map(String::from),
+            renamed_from: None,
+            renamed_at: None,
+            history: Vec::new(),
+            last_commit: None,
+            branch_health: None,
+            repo_history: Vec::new(),
+        };
+        self.backing_store.save_with_id(&repo, repo_name)?;
+
+        Ok(repo.stored())
+    }
 }
 
 impl Db for JsonStore {
     fn find_repo(&self, repo_name: &str) -> Option<StoredRepo> {
         let r = self.backing_store.get::<Repo>(repo_name);
 
-        r.map(|repo| StoredRepo {
-            id: repo.id,
-            title: repo.title,
-            created_at: Utc::now().naive_utc(),
-            updated_at: Utc::now().naive_utc(),
-        })
-        .ok()
+        r.map(|repo| repo.stored()).ok()
     }
 
     fn insert_tracked_items(
@@ -55,8 +197,16 @@ impl Db for JsonStore {
 
         if repo.is_ok() {
             let mut repo = repo.unwrap();
-            repo.items
-                .append(&mut items.into_iter().map(Item::from).collect());
+            for item in items.into_iter().map(StoredItem::from) {
+                match repo
+                    .items
+                    .iter_mut()
+                    .find(|i| i.foreign_id == item.foreign_id)
+                {
+                    Some(existing) => *existing = item,
+                    None => repo.items.push(item),
+                }
+            }
             self.backing_store
                 .save_with_id(&repo, &repo_name.title)
                 .map(|_arg| ())
@@ -79,7 +229,7 @@ impl Db for JsonStore {
         if repo.is_ok() {
             let mut repo = repo.unwrap();
 
-            repo.items.retain(|i| i.nr != target_nr);
+            repo.items.retain(|i| i.item.nr != target_nr);
 
             return self
                 .backing_store
@@ -97,30 +247,75 @@ impl Db for JsonStore {
         tree.map(|all| {
             all.into_iter()
                 .map(|(title, repo)| {
-                    let (issues, prs) = repo
+                    let (releases, items): (Vec<_>, Vec<_>) = repo
                         .items
                         .clone()
                         .into_iter()
-                        .map(|item| NewTrackedItem {
-                            title: item.title.clone(),
-                            state: State::Open, // TODO: Odd
-                            link: item.link.clone(),
-                            by: Author::new(item.by),
-                            labels: Label::map(&item.labels[..]),
-                            kind: item.kind.into(),
-                            foreign_id: "1234".into(), // TOOD Odd...
-                            last_updated: DateTime::parse_from_rfc3339(&item.last_updated)
-                                .unwrap()
-                                .with_timezone(&Utc),
-                            number: item.nr,
+                        .filter_map(|StoredItem { foreign_id, item }| {
+                            // A release or discussion missing what sets it apart is left out
+                            let details = match ItemKind::from(item.kind.clone()) {
+                                ItemKind::PR => ItemDetails::PR {
+                                    reviews: item.reviews.map(Reviews::from).unwrap_or_default(),
+                                    checks: item.checks.map(Checks::from).unwrap_or_default(),
+                                },
+                                ItemKind::Issue => ItemDetails::Issue,
+                                ItemKind::Release => {
+                                    ItemDetails::Release(Release::try_from(item.release?).ok()?)
+                                }
+                                ItemKind::Discussion => {
+                                    ItemDetails::Discussion(Discussion::from(item.discussion?))
+                                }
+                            };
+
+                            Some(NewTrackedItem {
+                                title: item.title.clone(),
+                                state: State::from(item.state.as_str()),
+                                link: item.link.clone(),
+                                by: Author::from(item.by),
+                                labels: item.labels.into_iter().map(Label::from).collect(),
+                                labels_truncated: item.labels_truncated,
+                                timeline: item
+                                    .timeline
+                                    .into_iter()
+                                    .filter_map(|event| TimelineEvent::try_from(event).ok())
+                                    .collect(),
+                                details,
+                                foreign_id,
+                                last_updated: DateTime::parse_from_rfc3339(&item.last_updated)
+                                    .unwrap()
+                                    .with_timezone(&Utc),
+                                number: item.nr,
+                                closed_at: item.closed_at.map(|at| {
+                                    DateTime::parse_from_rfc3339(&at)
+                                        .unwrap()
+                                        .with_timezone(&Utc)
+                                }),
+                                closed_by: item.closed_by.map(Author::new),
+                            })
                         })
-                        .partition(|i| i.kind == ItemKind::Issue);
+                        .partition(|i| i.kind() == ItemKind::Release);
+                    let (discussions, items): (Vec<_>, Vec<_>) = items
+                        .into_iter()
+                        .partition(|i| i.kind() == ItemKind::Discussion);
+                    let (issues, prs) =
+                        items.into_iter().partition(|i| i.kind() == ItemKind::Issue);
 
                     FullStoredRepo {
                         id: repo.id,
                         title,
+                        foreign_id: repo.foreign_id,
+                        renamed_from: repo.renamed_from,
+                        renamed_at: repo.renamed_at,
+                        last_commit: repo
+                            .last_commit
+                            .and_then(|commit| Commit::try_from(commit).ok()),
+                        branch_health: repo
+                            .branch_health
+                            .and_then(|health| BranchHealth::try_from(health).ok()),
                         issues,
                         prs,
+                        releases,
+                        discussions,
                     }
                 })
                 .collect()
@@ -129,28 +324,163 @@ impl Db for JsonStore {
     }
 
     fn insert_new_repo(&self, repo_name: &str) -> Result<StoredRepo, Error> {
-        let mut locked = self.next_id.lock().unwrap();
-        let next = locked.get_mut();
-        let id = *next + 1;
-        *next = id;
+        self.insert_repo(repo_name, None)
+    }
 
-        self.backing_store.save_with_id(
-            &Repo {
-                id,
-                title: repo_name.to_owned(),
-                items: Vec::new(),
-            },
-            repo_name,
-        )?;
+    fn insert_remote_repo(&self, remote: &RemoteRepo) -> Result<StoredRepo, Error> {
+        self.insert_repo(&remote.name.to_string(), Some(&remote.foreign_id))
+    }
 
-        let repo = StoredRepo {
-            id,
-            title: repo_name.to_owned(),
-            created_at: Utc::now().naive_utc(),
-            updated_at: Utc::now().naive_utc(),
-        };
+    fn set_repo_foreign_id(&self, repo: &StoredRepo, foreign_id: &str) -> Result<(), Error> {
+        let mut stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        stored.foreign_id = Some(foreign_id.to_string());
+        self.backing_store
+            .save_with_id(&stored, &repo.title)
+            .map(|_id| ())
+            .context("setting the foreign id of a repo")
+    }
+
+    fn record_events(&self, repo: &StoredRepo, events: Vec<ItemEvent>) -> Result<(), Error> {
+        let mut stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        stored
+            .history
+            .extend(events.into_iter().map(StoredEvent::from));
+        self.backing_store
+            .save_with_id(&stored, &repo.title)
+            .map(|_id| ())
+            .context("recording changes of items")
+    }
+
+    fn history(&self, repo: &StoredRepo, foreign_id: &str) -> Result<Vec<ItemEvent>, Error> {
+        let stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        stored
+            .history
+            .into_iter()
+            .filter(|event| event.foreign_id == foreign_id)
+            .map(|event| {
+                Ok(ItemEvent {
+                    change: event.change()?,
+                    foreign_id: event.foreign_id,
+                    number: event.number,
+                    recorded_at: event.recorded_at,
+                })
+            })
+            .collect()
+    }
+
+    fn item_foreign_id(&self, repo: &StoredRepo, number: i32) -> Result<Option<String>, Error> {
+        let stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        let release = ItemKind::Release.to_string();
+        let tracked = stored
+            .items
+            .iter()
+            .find(|i| i.item.nr == number && i.item.kind != release)
+            .map(|i| i.foreign_id.clone());
+
+        Ok(tracked.or_else(|| {
+            stored
+                .history
+                .iter()
+                .rev()
+                .find(|event| {
+                    event.number == number && event.change().is_ok_and(|c| !c.is_release())
+                })
+                .map(|event| event.foreign_id.clone())
+        }))
+    }
 
-        Ok(repo)
+    fn rename_repo(&self, repo: &StoredRepo, new_title: &str) -> Result<StoredRepo, Error> {
+        if self.find_repo(new_title).is_some() {
+            return Err(RepoAlreadyExists(new_title.to_string()).into());
+        }
+
+        let mut stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        stored.renamed_from = Some(stored.title.clone());
+        stored.renamed_at = Some(Utc::now().naive_utc());
+        stored.title = new_title.to_string();
+
+        self.backing_store
+            .save_with_id(&stored, new_title)
+            .context("saving a renamed repo")?;
+        self.backing_store
+            .delete(&repo.title)
+            .context("removing the old name of a renamed repo")?;
+
+        Ok(stored.stored())
+    }
+
+    fn set_last_commit(&self, repo: &StoredRepo, commit: Commit) -> Result<(), Error> {
+        let mut stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        stored.last_commit = Some(api::Commit::from(commit));
+        self.backing_store
+            .save_with_id(&stored, &repo.title)
+            .map(|_id| ())
+            .context("setting the last commit of a repo")
+    }
+
+    fn set_branch_health(&self, repo: &StoredRepo, health: BranchHealth) -> Result<(), Error> {
+        let mut stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        stored.branch_health = Some(api::BranchHealth::from(health));
+        self.backing_store
+            .save_with_id(&stored, &repo.title)
+            .map(|_id| ())
+            .context("setting the branch health of a repo")
+    }
+
+    fn record_repo_events(&self, repo: &StoredRepo, events: Vec<RepoEvent>) -> Result<(), Error> {
+        let mut stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        stored
+            .repo_history
+            .extend(events.into_iter().map(StoredRepoEvent::from));
+        self.backing_store
+            .save_with_id(&stored, &repo.title)
+            .map(|_id| ())
+            .context("recording changes of a repo")
+    }
+
+    fn repo_history(&self, repo: &StoredRepo) -> Result<Vec<RepoEvent>, Error> {
+        let stored = self
+            .backing_store
+            .get::<Repo>(&repo.title)
+            .with_context(|| format!("{} not found", repo.title))?;
+
+        stored
+            .repo_history
+            .into_iter()
+            .map(RepoEvent::try_from)
+            .collect()
     }
 
     fn delete(&self, repo: StoredRepo) -> Result<(), Error> {
@@ -179,5 +509,25 @@ mod tests {
         new(file_path)
     }
 
+    #[test]
+    fn reads_reviews_the_way_older_files_stored_them() {
+        let legacy = StoredEvent {
+            foreign_id: "node-1".to_string(),
+            number: 1,
+            change: "changes_requested".to_string(),
+            from: None,
+            to: Some("hubot".to_string()),
+            recorded_at: Utc::now(),
+        };
+
+        assert_eq!(
+            legacy.change().unwrap(),
+            Change::Reviewed {
+                by: "hubot".to_string(),
+                state: ReviewState::ChangesRequested,
+            }
+        );
+    }
+
     crate::behaves_like_a_db!(setup);
 }
@@ -6,7 +6,7 @@ use chrono::{NaiveDateTime, Utc};
 // TODO: this needs to move away
 use schema::repos;
 
-use crate::domain::NewTrackedItem;
+use crate::domain::{BranchHealth, Commit, ItemEvent, NewTrackedItem, RemoteRepo, RepoEvent};
 
 mod in_memory;
 mod json_storage;
@@ -33,6 +33,26 @@ pub trait Db: Send + Sync {
     fn remove_tracked_item(&self, repo: &StoredRepo, item: NewTrackedItem) -> Result<()>;
     fn all(&self) -> Result<Vec<FullStoredRepo>>;
     fn insert_new_repo(&self, repo_name: &str) -> Result<StoredRepo>;
+    /// Adds a repo under the name GitHub knows it by, along with its node ID.
+    fn insert_remote_repo(&self, remote: &RemoteRepo) -> Result<StoredRepo>;
+    /// Remembers GitHub's node ID for the repo, which stays the same across renames.
+    fn set_repo_foreign_id(&self, repo: &StoredRepo, foreign_id: &str) -> Result<()>;
+    /// Changes the title of a repo that was renamed or transferred on GitHub,
+    /// keeping its tracked items and noting the old title.
+    fn rename_repo(&self, repo: &StoredRepo, new_title: &str) -> Result<StoredRepo>;
+    fn record_events(&self, repo: &StoredRepo, events: Vec<ItemEvent>) -> Result<()>;
+    /// Everything recorded about an issue or PR of the repo, oldest first.
+    fn history(&self, repo: &StoredRepo, foreign_id: &str) -> Result<Vec<ItemEvent>>;
+    /// The node ID of the issue, PR or discussion with that number, whether it is still
+    /// tracked or only its history is left. Releases are numbered differently and left out.
+    fn item_foreign_id(&self, repo: &StoredRepo, number: i32) -> Result<Option<String>>;
+    /// Remembers the latest commit on the default branch of the repo, replacing the previous one.
+    fn set_last_commit(&self, repo: &StoredRepo, commit: Commit) -> Result<()>;
+    /// Remembers whether the default branch of the repo is green, replacing what was known before.
+    fn set_branch_health(&self, repo: &StoredRepo, health: BranchHealth) -> Result<()>;
+    fn record_repo_events(&self, repo: &StoredRepo, events: Vec<RepoEvent>) -> Result<()>;
+    /// Everything recorded about the repo as a whole, oldest first.
+    fn repo_history(&self, repo: &StoredRepo) -> Result<Vec<RepoEvent>>;
     fn delete(&self, repo: StoredRepo) -> Result<()>;
 }
 
@@ -43,6 +63,9 @@ pub struct StoredRepo {
     pub title: String,
     created_at: NaiveDateTime,
     updated_at: NaiveDateTime,
+    pub foreign_id: Option<String>,
+    pub renamed_from: Option<String>,
+    pub renamed_at: Option<NaiveDateTime>,
 }
 
 impl StoredRepo {
@@ -58,6 +81,9 @@ impl StoredRepo {
             title: title.into(),
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
+            foreign_id: None,
+            renamed_from: None,
+            renamed_at: None,
         }
     }
 }
@@ -66,8 +92,15 @@ impl StoredRepo {
 pub struct FullStoredRepo {
     pub id: i32,
     pub title: String,
+    pub foreign_id: Option<String>,
+    pub renamed_from: Option<String>,
+    pub renamed_at: Option<NaiveDateTime>,
+    pub last_commit: Option<Commit>,
+    pub branch_health: Option<BranchHealth>,
     pub issues: Vec<NewTrackedItem>,
     pub prs: Vec<NewTrackedItem>,
+    pub releases: Vec<NewTrackedItem>,
+    pub discussions: Vec<NewTrackedItem>,
 }
 
 impl FullStoredRepo {
@@ -78,6 +111,9 @@ impl FullStoredRepo {
             // TODO: get rid of some of these...
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
+            foreign_id: self.foreign_id.clone(),
+            renamed_from: self.renamed_from.clone(),
+            renamed_at: self.renamed_at,
         }
     }
 
@@ -86,6 +122,8 @@ impl FullStoredRepo {
 
         res.append(&mut self.issues.clone());
         res.append(&mut self.prs.clone());
+        res.append(&mut self.releases.clone());
+        res.append(&mut self.discussions.clone());
 
         res
     }
@@ -95,12 +133,40 @@ impl FullStoredRepo {
 #[table_name = "repos"]
 pub struct NewRepo<'a> {
     pub title: &'a str,
+    pub foreign_id: Option<&'a str>,
 }
 
 mod support {
+    /// An open PR that tests change whatever they care about on.
+    #[cfg(test)]
+    pub fn tracked_item(foreign_id: &str, nr: i32) -> crate::domain::NewTrackedItem {
+        use crate::domain::{Author, Checks, ItemDetails, NewTrackedItem, Reviews, State};
+
+        NewTrackedItem {
+            title: "some PR".to_string(),
+            state: State::Open,
+            link: "http://foo.bar".to_string(),
+            by: Author::new("Steve Hawking"),
+            labels: vec![],
+            labels_truncated: false,
+            timeline: vec![],
+            details: ItemDetails::PR {
+                reviews: Reviews::default(),
+                checks: Checks::default(),
+            },
+            foreign_id: foreign_id.to_string(),
+            last_updated: chrono::Utc::now(),
+            number: nr,
+            closed_at: None,
+            closed_by: None,
+        }
+    }
+
     #[macro_export]
     macro_rules! behaves_like_a_db {
         ($setup_db:expr) => {
+            use $crate::db::support::tracked_item;
+
             #[test]
             fn it_finds_a_repo_that_was_added() {
                 let db = $setup_db();
@@ -115,6 +181,96 @@ mod support {
                 assert_eq!(found2.unwrap().title, "other")
             }
 
+            #[test]
+            fn keeps_the_node_id_of_a_repo_added_from_github() {
+                use $crate::domain::{RemoteRepo, RepoAlreadyExists, RepoName};
+                let db = $setup_db();
+
+                let remote = RemoteRepo {
+                    foreign_id: "repo-1".to_string(),
+                    name: RepoName::from("foo/bar").unwrap(),
+                };
+                let repo = db.insert_remote_repo(&remote).unwrap();
+
+                assert_eq!(repo.title, "foo/bar");
+                assert_eq!(repo.foreign_id.as_deref(), Some("repo-1"));
+                let found = db.find_repo("foo/bar").unwrap();
+                assert_eq!(found.foreign_id.as_deref(), Some("repo-1"));
+                assert!(db
+                    .insert_remote_repo(&remote)
+                    .unwrap_err()
+                    .is::<RepoAlreadyExists>());
+            }
+
+            #[test]
+            fn a_repo_can_not_be_added_twice() {
+                use $crate::domain::RepoAlreadyExists;
+
+                let db = $setup_db();
+
+                db.insert_new_repo("foo/bar").unwrap();
+                let second = db.insert_new_repo("foo/bar");
+
+                assert!(second.unwrap_err().is::<RepoAlreadyExists>());
+                assert_eq!(db.all().unwrap().len(), 1);
+            }
+
+            #[test]
+            fn remembers_the_foreign_id_of_a_repo() {
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("foo/bar").unwrap();
+                assert_eq!(repo.foreign_id, None);
+
+                db.set_repo_foreign_id(&repo, "MDEwOlJlcG9zaXRvcnkx")
+                    .unwrap();
+
+                let found = db.find_repo("foo/bar").unwrap();
+                assert_eq!(found.foreign_id, Some("MDEwOlJlcG9zaXRvcnkx".to_string()));
+                assert_eq!(
+                    db.all().unwrap()[0].foreign_id,
+                    Some("MDEwOlJlcG9zaXRvcnkx".to_string())
+                );
+            }
+
+            #[test]
+            fn a_renamed_repo_keeps_its_items_and_its_old_title() {
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("foo/bar").unwrap();
+                db.insert_tracked_items(&repo, vec![tracked_item("sflhjsfklhjsd", 1)])
+                    .unwrap();
+
+                let renamed = db.rename_repo(&repo, "new-owner/bar").unwrap();
+
+                assert_eq!(renamed.id, repo.id);
+                assert_eq!(renamed.title, "new-owner/bar");
+                assert_eq!(renamed.renamed_from, Some("foo/bar".to_string()));
+                assert!(renamed.renamed_at.is_some());
+                assert!(db.find_repo("foo/bar").is_none());
+
+                let all = db.all().unwrap();
+                assert_eq!(all.len(), 1);
+                assert_eq!(all[0].title, "new-owner/bar");
+                assert_eq!(all[0].renamed_from, Some("foo/bar".to_string()));
+                assert_eq!(all[0].items().len(), 1);
+            }
+
+            #[test]
+            fn a_repo_can_not_be_renamed_onto_another_tracked_repo() {
+                use $crate::domain::RepoAlreadyExists;
+
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("foo/bar").unwrap();
+                db.insert_new_repo("foo/baz").unwrap();
+
+                let renamed = db.rename_repo(&repo, "foo/baz");
+
+                assert!(renamed.unwrap_err().is::<RepoAlreadyExists>());
+                assert!(db.find_repo("foo/bar").is_some());
+            }
+
             #[test]
             fn a_deleted_item_can_not_be_found() {
                 let db = $setup_db();
@@ -131,28 +287,12 @@ mod support {
 
             #[test]
             fn can_add_an_item_to_a_repo() {
-                use crate::domain::{Author, ItemKind, NewTrackedItem, State};
-                use chrono::Utc;
-
                 let db = $setup_db();
 
                 let repo = db.insert_new_repo("foo/bar").unwrap();
 
-                db.insert_tracked_items(
-                    &repo,
-                    vec![NewTrackedItem {
-                        title: "some PR".to_string(),
-                        state: State::Open,
-                        link: "http://foo.bar".to_string(),
-                        by: Author::new("Steve Hawking"),
-                        labels: vec![],
-                        kind: ItemKind::PR,
-                        foreign_id: "sflhjsfklhjsd".to_string(),
-                        last_updated: Utc::now(),
-                        number: 1,
-                    }],
-                )
-                .expect("should have been able to insert tracked items");
+                db.insert_tracked_items(&repo, vec![tracked_item("sflhjsfklhjsd", 1)])
+                    .expect("should have been able to insert tracked items");
 
                 let all = db.all().unwrap();
 
@@ -166,40 +306,19 @@ mod support {
 
             #[test]
             fn can_update_an_added_item() {
-                use crate::domain::{Author, ItemKind, NewTrackedItem, State};
-                use chrono::Utc;
+                use $crate::domain::NewTrackedItem;
                 let db = $setup_db();
 
                 let repo = db.insert_new_repo("totally/madeup").unwrap();
 
-                db.insert_tracked_items(
-                    &repo,
-                    vec![NewTrackedItem {
-                        title: "some PR".to_string(),
-                        state: State::Open,
-                        link: "http://foo.bar".to_string(),
-                        by: Author::new("Steve Hawking"),
-                        labels: vec![],
-                        kind: ItemKind::PR,
-                        foreign_id: "sflhjsfklhjsd".to_string(),
-                        last_updated: Utc::now(),
-                        number: 1,
-                    }],
-                )
-                .unwrap();
+                db.insert_tracked_items(&repo, vec![tracked_item("sflhjsfklhjsd", 1)])
+                    .unwrap();
 
                 db.update_tracked_item(
                     &repo,
                     NewTrackedItem {
                         title: "changed-the-title".to_string(),
-                        state: State::Open,
-                        link: "http://foo.bar".to_string(),
-                        by: Author::new("Steve Hawking"),
-                        labels: vec![],
-                        kind: ItemKind::PR,
-                        foreign_id: "sflhjsfklhjsd".to_string(),
-                        last_updated: Utc::now(),
-                        number: 1,
+                        ..tracked_item("sflhjsfklhjsd", 1)
                     },
                 )
                 .unwrap();
@@ -218,38 +337,717 @@ mod support {
                 )
             }
 
+            #[test]
+            fn keeps_the_foreign_id_of_tracked_items() {
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("foo/bar").unwrap();
+                db.insert_tracked_items(&repo, vec![tracked_item("MDExOlB1bGxSZXF1ZXN0MQ==", 1)])
+                    .unwrap();
+
+                let all = db.all().unwrap();
+                let matching_repo = all.iter().find(|r| r.title == repo.title).unwrap();
+
+                assert_eq!(
+                    matching_repo.items()[0].foreign_id,
+                    "MDExOlB1bGxSZXF1ZXN0MQ=="
+                );
+            }
+
+            #[test]
+            fn tracking_an_item_twice_keeps_a_single_copy() {
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("foo/bar").unwrap();
+                let item = tracked_item("sflhjsfklhjsd", 1);
+
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
+                db.insert_tracked_items(&repo, vec![item.clone(), item.clone()])
+                    .unwrap();
+
+                let all = db.all().unwrap();
+                let matching_repo = all.iter().find(|r| r.title == repo.title).unwrap();
+
+                assert_eq!(matching_repo.items().len(), 1);
+            }
+
+            #[test]
+            fn tracking_an_item_again_refreshes_it() {
+                use $crate::domain::NewTrackedItem;
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("foo/bar").unwrap();
+                let item = tracked_item("sflhjsfklhjsd", 1);
+
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
+                db.insert_tracked_items(
+                    &repo,
+                    vec![NewTrackedItem {
+                        title: "a better title".to_string(),
+                        ..item
+                    }],
+                )
+                .unwrap();
+
+                let all = db.all().unwrap();
+                let matching_repo = all.iter().find(|r| r.title == repo.title).unwrap();
+
+                assert_eq!(matching_repo.items().len(), 1);
+                assert_eq!(matching_repo.items()[0].title, "a better title");
+            }
+
+            #[test]
+            fn the_same_item_can_be_tracked_in_different_repos() {
+                let db = $setup_db();
+
+                let first = db.insert_new_repo("foo/bar").unwrap();
+                let second = db.insert_new_repo("foo/baz").unwrap();
+                let item = tracked_item("sflhjsfklhjsd", 1);
+
+                db.insert_tracked_items(&first, vec![item.clone()]).unwrap();
+                db.insert_tracked_items(&second, vec![item]).unwrap();
+
+                let all = db.all().unwrap();
+                assert!(all.iter().all(|r| r.items().len() == 1));
+            }
+
+            #[test]
+            fn untracking_an_item_in_one_repo_keeps_it_in_the_other() {
+                let db = $setup_db();
+
+                let first = db.insert_new_repo("foo/bar").unwrap();
+                let second = db.insert_new_repo("foo/baz").unwrap();
+                let item = tracked_item("sflhjsfklhjsd", 1);
+
+                db.insert_tracked_items(&first, vec![item.clone()]).unwrap();
+                db.insert_tracked_items(&second, vec![item.clone()])
+                    .unwrap();
+                db.remove_tracked_item(&first, item).unwrap();
+
+                let all = db.all().unwrap();
+                let items = |title: &str| all.iter().find(|r| r.title == title).unwrap().items();
+                assert!(items("foo/bar").is_empty());
+                assert_eq!(items("foo/baz").len(), 1);
+            }
+
+            #[test]
+            fn updating_an_item_in_one_repo_leaves_the_other_alone() {
+                use $crate::domain::NewTrackedItem;
+                let db = $setup_db();
+
+                let first = db.insert_new_repo("foo/bar").unwrap();
+                let second = db.insert_new_repo("foo/baz").unwrap();
+                let item = tracked_item("sflhjsfklhjsd", 1);
+
+                db.insert_tracked_items(&first, vec![item.clone()]).unwrap();
+                db.insert_tracked_items(&second, vec![item.clone()])
+                    .unwrap();
+                db.update_tracked_item(
+                    &first,
+                    NewTrackedItem {
+                        title: "renamed".to_string(),
+                        ..item
+                    },
+                )
+                .unwrap();
+
+                let all = db.all().unwrap();
+                let items = |title: &str| all.iter().find(|r| r.title == title).unwrap().items();
+                assert_eq!(items("foo/bar")[0].title, "renamed");
+                assert_eq!(items("foo/baz")[0].title, "some PR");
+            }
+
+            #[test]
+            fn keeps_the_state_of_tracked_items() {
+                use $crate::domain::{NewTrackedItem, State};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("foo/bar").unwrap();
+                let item = NewTrackedItem {
+                    state: State::Draft,
+                    ..tracked_item("sflhjsfklhjsd", 1)
+                };
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
+
+                let all = db.all().unwrap();
+                assert_eq!(all[0].items()[0].state, State::Draft);
+
+                db.update_tracked_item(
+                    &repo,
+                    NewTrackedItem {
+                        state: State::Open,
+                        ..item
+                    },
+                )
+                .unwrap();
+
+                let all = db.all().unwrap();
+                assert_eq!(all[0].items()[0].state, State::Open);
+            }
+
+            #[test]
+            fn keeps_when_and_by_whom_an_item_was_closed() {
+                use chrono::{TimeZone, Utc};
+                use $crate::domain::{Author, NewTrackedItem, State};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("foo/bar").unwrap();
+                let item = tracked_item("sflhjsfklhjsd", 1);
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
+
+                let closed_at = Utc.ymd(2020, 2, 24).and_hms(18, 0, 0);
+                db.update_tracked_item(
+                    &repo,
+                    NewTrackedItem {
+                        state: State::Merged,
+                        closed_at: Some(closed_at),
+                        closed_by: Some(Author::new("Ada Lovelace")),
+                        ..item
+                    },
+                )
+                .unwrap();
+
+                let stored = db.all().unwrap()[0].items()[0].clone();
+                assert_eq!(stored.state, State::Merged);
+                assert_eq!(stored.closed_at, Some(closed_at));
+                assert_eq!(stored.closed_by.unwrap().name, "Ada Lovelace");
+            }
+
             #[test]
             fn an_added_tracked_item_can_be_removed() {
-                use crate::domain::{Author, ItemKind, NewTrackedItem, State};
-                use chrono::Utc;
                 let db = $setup_db();
 
                 let repo = db.insert_new_repo("abc/123").unwrap();
-                let tracked_item = NewTrackedItem {
-                    title: "some PR".to_string(),
-                    state: State::Open,
-                    link: "http://foo.bar".to_string(),
-                    by: Author::new("Steve Hawking"),
-                    labels: vec![],
-                    kind: ItemKind::PR,
-                    foreign_id: "sflhjsfklhjsd".to_string(),
-                    last_updated: Utc::now(),
-                    number: 1,
-                };
+                let item = tracked_item("sflhjsfklhjsd", 1);
 
-                db.insert_tracked_items(&repo, vec![tracked_item.clone()])
-                    .unwrap();
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
 
                 let all = db.all().unwrap();
                 let matching_repo = all.iter().find(|r| r.title == repo.title).unwrap();
                 assert_eq!(matching_repo.items().len(), 1);
 
-                db.remove_tracked_item(&repo, tracked_item).unwrap();
+                db.remove_tracked_item(&repo, item).unwrap();
 
                 let all = db.all().unwrap();
                 let matching_repo = all.iter().find(|r| r.title == repo.title).unwrap();
                 assert_eq!(matching_repo.items().len(), 0);
             }
+
+            #[test]
+            fn removing_a_tracked_item_leaves_the_others_alone() {
+                use $crate::domain::{ItemDetails, NewTrackedItem};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let item = |nr: i32| NewTrackedItem {
+                    title: format!("item {}", nr),
+                    details: ItemDetails::Issue,
+                    ..tracked_item(&format!("node-{}", nr), nr)
+                };
+
+                db.insert_tracked_items(&repo, vec![item(1), item(2), item(3)])
+                    .unwrap();
+                db.remove_tracked_item(&repo, item(2)).unwrap();
+
+                let all = db.all().unwrap();
+                let mut left = all[0].items().iter().map(|i| i.number).collect::<Vec<_>>();
+                left.sort();
+                assert_eq!(left, vec![1, 3]);
+            }
+
+            #[test]
+            fn keeps_labels_with_their_color_and_description() {
+                use $crate::domain::{ItemDetails, Label, NewTrackedItem};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let bug = Label {
+                    name: "bug".to_string(),
+                    color: Some("d73a4a".to_string()),
+                    description: Some("Something isn't working".to_string()),
+                };
+                let odd = Label::new("needs: design, docs".to_string());
+                let mut item = NewTrackedItem {
+                    title: "Crash on start".to_string(),
+                    labels: vec![bug.clone(), odd.clone()],
+                    details: ItemDetails::Issue,
+                    ..tracked_item("node-1", 1)
+                };
+
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
+                assert_eq!(
+                    db.all().unwrap()[0].items()[0].labels,
+                    vec![bug.clone(), odd]
+                );
+
+                item.labels = vec![bug.clone()];
+                item.labels_truncated = true;
+                db.update_tracked_item(&repo, item).unwrap();
+                let all = db.all().unwrap();
+                assert_eq!(all[0].items()[0].labels, vec![bug]);
+                assert!(all[0].items()[0].labels_truncated);
+            }
+
+            #[test]
+            fn keeps_the_reviews_of_prs() {
+                use chrono::{TimeZone, Utc};
+                use $crate::domain::{
+                    Checks, ItemDetails, NewTrackedItem, Review, ReviewDecision, ReviewState,
+                    Reviews,
+                };
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let reviews = Reviews {
+                    decision: Some(ReviewDecision::ChangesRequested),
+                    requested: vec!["octocat".to_string(), "some-org/reviewers".to_string()],
+                    latest: vec![Review {
+                        by: "hubot".to_string(),
+                        state: ReviewState::ChangesRequested,
+                        submitted_at: Some(Utc.ymd(2020, 2, 29).and_hms(12, 0, 0)),
+                    }],
+                };
+                let mut item = NewTrackedItem {
+                    title: "Speed things up".to_string(),
+                    details: ItemDetails::PR {
+                        reviews: reviews.clone(),
+                        checks: Checks::default(),
+                    },
+                    ..tracked_item("node-1", 1)
+                };
+
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
+                assert_eq!(
+                    db.all().unwrap()[0].items()[0].details.reviews(),
+                    Some(&reviews)
+                );
+
+                item.details = ItemDetails::PR {
+                    reviews: Reviews::default(),
+                    checks: Checks::default(),
+                };
+                db.update_tracked_item(&repo, item).unwrap();
+                assert_eq!(
+                    db.all().unwrap()[0].items()[0].details.reviews(),
+                    Some(&Reviews::default())
+                );
+            }
+
+            #[test]
+            fn keeps_the_checks_of_prs() {
+                use $crate::domain::{Checks, CiStatus, ItemDetails, NewTrackedItem, Reviews};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let checks = Checks {
+                    status: Some(CiStatus::Failing),
+                    failing: vec!["build".to_string(), "ci/lint".to_string()],
+                };
+                let mut item = NewTrackedItem {
+                    title: "Speed things up".to_string(),
+                    details: ItemDetails::PR {
+                        reviews: Reviews::default(),
+                        checks: checks.clone(),
+                    },
+                    ..tracked_item("node-1", 1)
+                };
+
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
+                assert_eq!(
+                    db.all().unwrap()[0].items()[0].details.checks(),
+                    Some(&checks)
+                );
+
+                item.details = ItemDetails::PR {
+                    reviews: Reviews::default(),
+                    checks: Checks {
+                        status: Some(CiStatus::Passing),
+                        failing: vec![],
+                    },
+                };
+                db.update_tracked_item(&repo, item.clone()).unwrap();
+                assert_eq!(db.all().unwrap()[0].items()[0].details, item.details);
+            }
+
+            #[test]
+            fn keeps_the_timeline_of_items() {
+                use chrono::{TimeZone, Utc};
+                use $crate::domain::{
+                    ItemDetails, NewTrackedItem, TimelineEvent, TimelineEventKind,
+                };
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let commented = TimelineEvent {
+                    kind: TimelineEventKind::Commented,
+                    by: "octocat".to_string(),
+                    at: Utc.ymd(2020, 3, 1).and_hms(10, 0, 0),
+                    detail: Some("Can reproduce".to_string()),
+                };
+                let closed = TimelineEvent {
+                    kind: TimelineEventKind::Closed,
+                    by: "hubot".to_string(),
+                    at: Utc.ymd(2020, 3, 2).and_hms(10, 0, 0),
+                    detail: None,
+                };
+                let mut item = NewTrackedItem {
+                    title: "Crashes on start".to_string(),
+                    timeline: vec![commented.clone()],
+                    details: ItemDetails::Issue,
+                    ..tracked_item("node-1", 1)
+                };
+
+                db.insert_tracked_items(&repo, vec![item.clone()]).unwrap();
+                assert_eq!(
+                    db.all().unwrap()[0].items()[0].timeline,
+                    vec![commented.clone()]
+                );
+
+                item.timeline = vec![commented.clone(), closed.clone()];
+                db.update_tracked_item(&repo, item).unwrap();
+                assert_eq!(
+                    db.all().unwrap()[0].items()[0].timeline,
+                    vec![commented, closed]
+                );
+            }
+
+            #[test]
+            fn keeps_the_last_commit_of_a_repo() {
+                use chrono::{TimeZone, Utc};
+                use $crate::domain::{CiStatus, Commit};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                assert_eq!(db.all().unwrap()[0].last_commit, None);
+
+                let first = Commit {
+                    branch: "main".to_string(),
+                    sha: "8a2f1e0".to_string(),
+                    headline: "Speed up the build".to_string(),
+                    author: "octocat".to_string(),
+                    committed_at: Utc.ymd(2020, 3, 3).and_hms(8, 0, 0),
+                    ci_status: None,
+                };
+                let second = Commit {
+                    sha: "c0ffee1".to_string(),
+                    headline: "Fix the build".to_string(),
+                    committed_at: Utc.ymd(2020, 3, 3).and_hms(9, 0, 0),
+                    ci_status: Some(CiStatus::Passing),
+                    ..first.clone()
+                };
+
+                db.set_last_commit(&repo, first).unwrap();
+                db.set_last_commit(&repo, second.clone()).unwrap();
+
+                assert_eq!(db.all().unwrap()[0].last_commit, Some(second));
+            }
+
+            #[test]
+            fn keeps_the_health_of_the_default_branch() {
+                use chrono::{TimeZone, Utc};
+                use $crate::domain::{BranchHealth, CiStatus};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                assert_eq!(db.all().unwrap()[0].branch_health, None);
+
+                let broken = BranchHealth {
+                    status: CiStatus::Failing,
+                    since: None,
+                };
+                let fixed = BranchHealth {
+                    status: CiStatus::Passing,
+                    since: Some(Utc.ymd(2020, 3, 6).and_hms(11, 30, 0)),
+                };
+
+                db.set_branch_health(&repo, broken.clone()).unwrap();
+                assert_eq!(db.all().unwrap()[0].branch_health, Some(broken));
+
+                db.set_branch_health(&repo, fixed.clone()).unwrap();
+                assert_eq!(db.all().unwrap()[0].branch_health, Some(fixed));
+
+                db.delete(repo).unwrap();
+                db.insert_new_repo("abc/123").unwrap();
+                assert_eq!(db.all().unwrap()[0].branch_health, None);
+            }
+
+            #[test]
+            fn keeps_the_history_of_each_repo_oldest_first() {
+                use chrono::{Duration, TimeZone, Utc};
+                use $crate::domain::{CiStatus, RepoChange, RepoEvent};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let other = db.insert_new_repo("def/456").unwrap();
+                let then = Utc.ymd(2020, 3, 7).and_hms(9, 0, 0);
+                let event = |from: CiStatus, to: CiStatus, minutes: i64| RepoEvent {
+                    change: RepoChange::BranchHealth { from, to },
+                    recorded_at: then + Duration::minutes(minutes),
+                };
+                let broken = event(CiStatus::Passing, CiStatus::Failing, 0);
+                let fixed = event(CiStatus::Failing, CiStatus::Passing, 30);
+
+                db.record_repo_events(&repo, vec![broken.clone()]).unwrap();
+                db.record_repo_events(&other, vec![broken.clone()]).unwrap();
+                db.record_repo_events(&repo, vec![fixed.clone()]).unwrap();
+
+                assert_eq!(db.repo_history(&repo).unwrap(), vec![broken, fixed]);
+
+                db.delete(repo).unwrap();
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                assert!(db.repo_history(&repo).unwrap().is_empty());
+                assert_eq!(db.repo_history(&other).unwrap().len(), 1);
+            }
+
+            #[test]
+            fn keeps_releases_apart_from_issues_and_prs() {
+                use chrono::{TimeZone, Utc};
+                use $crate::domain::{Author, ItemDetails, NewTrackedItem, Release};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let published_at = Utc.ymd(2020, 3, 4).and_hms(9, 0, 0);
+                let release = NewTrackedItem {
+                    title: "Version 2.0 beta".to_string(),
+                    link: "https://github.com/abc/123/releases/tag/v2.0.0-beta.1".to_string(),
+                    by: Author::new("octocat"),
+                    details: ItemDetails::Release(Release {
+                        tag: "v2.0.0-beta.1".to_string(),
+                        prerelease: true,
+                        published_at,
+                    }),
+                    last_updated: published_at,
+                    ..tracked_item("release-1", 1234)
+                };
+
+                db.insert_tracked_items(&repo, vec![release.clone()])
+                    .unwrap();
+
+                let all = db.all().unwrap();
+                assert!(all[0].prs.is_empty());
+                assert!(all[0].issues.is_empty());
+                assert_eq!(all[0].releases.len(), 1);
+                assert_eq!(all[0].releases[0].title, "Version 2.0 beta");
+                assert_eq!(all[0].releases[0].details, release.details);
+
+                let promoted = NewTrackedItem {
+                    details: ItemDetails::Release(Release {
+                        prerelease: false,
+                        ..release.details.release().unwrap().clone()
+                    }),
+                    ..release
+                };
+                db.update_tracked_item(&repo, promoted.clone()).unwrap();
+
+                assert_eq!(db.all().unwrap()[0].releases[0].details, promoted.details);
+            }
+
+            #[test]
+            fn keeps_discussions_apart_from_issues_and_prs() {
+                use chrono::{TimeZone, Utc};
+                use $crate::domain::{Author, Discussion, ItemDetails, NewTrackedItem};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let discussion = NewTrackedItem {
+                    title: "How do I configure this?".to_string(),
+                    link: "https://github.com/abc/123/discussions/7".to_string(),
+                    by: Author::new("octocat"),
+                    details: ItemDetails::Discussion(Discussion {
+                        category: "Q&A".to_string(),
+                        answered: false,
+                        comments: 2,
+                    }),
+                    last_updated: Utc.ymd(2020, 3, 5).and_hms(9, 0, 0),
+                    ..tracked_item("discussion-7", 7)
+                };
+
+                db.insert_tracked_items(&repo, vec![discussion.clone()])
+                    .unwrap();
+
+                let all = db.all().unwrap();
+                assert!(all[0].issues.is_empty());
+                assert_eq!(all[0].discussions.len(), 1);
+                assert_eq!(all[0].discussions[0].details, discussion.details);
+
+                let answered = NewTrackedItem {
+                    details: ItemDetails::Discussion(Discussion {
+                        answered: true,
+                        comments: 3,
+                        ..discussion.details.discussion().unwrap().clone()
+                    }),
+                    ..discussion
+                };
+                db.update_tracked_item(&repo, answered.clone()).unwrap();
+
+                assert_eq!(
+                    db.all().unwrap()[0].discussions[0].details,
+                    answered.details
+                );
+            }
+
+            #[test]
+            fn keeps_who_authored_an_item() {
+                use $crate::domain::{Author, AuthorKind, NewTrackedItem};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let item = NewTrackedItem {
+                    title: "Bump serde".to_string(),
+                    by: Author::new("dependabot")
+                        .with_link("https://github.com/apps/dependabot")
+                        .with_avatar("https://avatars.example.com/dependabot")
+                        .with_kind(AuthorKind::Bot),
+                    ..tracked_item("node-1", 1)
+                };
+
+                db.insert_tracked_items(&repo, vec![item]).unwrap();
+
+                let all = db.all().unwrap();
+                let by = &all[0].items()[0].by;
+                assert_eq!(by.name, "dependabot");
+                assert_eq!(
+                    by.link.as_deref(),
+                    Some("https://github.com/apps/dependabot")
+                );
+                assert_eq!(
+                    by.avatar.as_deref(),
+                    Some("https://avatars.example.com/dependabot")
+                );
+                assert_eq!(by.kind, AuthorKind::Bot);
+            }
+
+            #[test]
+            fn keeps_the_history_of_each_item_oldest_first() {
+                use chrono::{Duration, TimeZone, Utc};
+                use $crate::domain::{Change, ItemEvent, State};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                let then = Utc.ymd(2020, 2, 25).and_hms(12, 0, 0);
+                let event = |nr: i32, change: Change, minutes: i64| ItemEvent {
+                    foreign_id: format!("node-{}", nr),
+                    number: nr,
+                    change,
+                    recorded_at: then + Duration::minutes(minutes),
+                };
+
+                db.record_events(
+                    &repo,
+                    vec![
+                        event(1, Change::LabelAdded("bug".to_string()), 0),
+                        event(2, Change::LabelRemoved("bug".to_string()), 0),
+                    ],
+                )
+                .unwrap();
+                db.record_events(
+                    &repo,
+                    vec![
+                        event(
+                            1,
+                            Change::State {
+                                from: State::Open,
+                                to: State::Closed,
+                            },
+                            5,
+                        ),
+                        event(
+                            1,
+                            Change::Updated {
+                                from: then,
+                                to: then + Duration::minutes(5),
+                            },
+                            5,
+                        ),
+                    ],
+                )
+                .unwrap();
+
+                let history = db.history(&repo, "node-1").unwrap();
+                let changes = history.iter().map(|e| e.change.clone()).collect::<Vec<_>>();
+                assert_eq!(
+                    changes,
+                    vec![
+                        Change::LabelAdded("bug".to_string()),
+                        Change::State {
+                            from: State::Open,
+                            to: State::Closed,
+                        },
+                        Change::Updated {
+                            from: then,
+                            to: then + Duration::minutes(5),
+                        },
+                    ]
+                );
+                assert_eq!(history[1].recorded_at, then + Duration::minutes(5));
+                assert!(db.history(&repo, "node-3").unwrap().is_empty());
+            }
+
+            #[test]
+            fn keeps_histories_apart_even_when_numbers_are_the_same() {
+                use chrono::Utc;
+                use $crate::domain::{Change, ItemEvent};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                // Releases are numbered by their database ID, which can match an issue number
+                let event = |foreign_id: &str, change: Change| ItemEvent {
+                    foreign_id: foreign_id.to_string(),
+                    number: 7,
+                    change,
+                    recorded_at: Utc::now(),
+                };
+
+                db.record_events(
+                    &repo,
+                    vec![
+                        event("issue-7", Change::LabelAdded("bug".to_string())),
+                        event("release-7", Change::Released("v1.0.0".to_string())),
+                    ],
+                )
+                .unwrap();
+
+                let history = db.history(&repo, "release-7").unwrap();
+                assert_eq!(history.len(), 1);
+                assert_eq!(history[0].change, Change::Released("v1.0.0".to_string()));
+            }
+
+            #[test]
+            fn finds_items_by_number_while_tracked_and_after() {
+                use chrono::Utc;
+                use $crate::domain::{Change, ItemEvent};
+                let db = $setup_db();
+
+                let repo = db.insert_new_repo("abc/123").unwrap();
+                db.insert_tracked_items(&repo, vec![tracked_item("issue-1", 1)])
+                    .unwrap();
+                let event = |foreign_id: &str, number: i32, change: Change| ItemEvent {
+                    foreign_id: foreign_id.to_string(),
+                    number,
+                    change,
+                    recorded_at: Utc::now(),
+                };
+                db.record_events(
+                    &repo,
+                    vec![
+                        event("issue-2", 2, Change::LabelAdded("bug".to_string())),
+                        event("release-3", 3, Change::Released("v1.0.0".to_string())),
+                    ],
+                )
+                .unwrap();
+
+                assert_eq!(
+                    db.item_foreign_id(&repo, 1).unwrap().as_deref(),
+                    Some("issue-1")
+                );
+                // No longer tracked, but its history is still around
+                assert_eq!(
+                    db.item_foreign_id(&repo, 2).unwrap().as_deref(),
+                    Some("issue-2")
+                );
+                assert!(db.item_foreign_id(&repo, 3).unwrap().is_none());
+                assert!(db.item_foreign_id(&repo, 4).unwrap().is_none());
+            }
         };
     }
 }
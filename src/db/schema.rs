@@ -1,9 +1,95 @@
+table! {
+    branch_health (repo_id) {
+        repo_id -> Integer,
+        status -> Text,
+        changed_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    item_events (id) {
+        id -> Integer,
+        repo_id -> Integer,
+        foreign_id -> Text,
+        number -> Integer,
+        change -> Text,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+        recorded_at -> Timestamp,
+    }
+}
+
+table! {
+    item_failing_checks (id) {
+        id -> Integer,
+        item_id -> Integer,
+        name -> Text,
+    }
+}
+
+table! {
+    item_labels (id) {
+        id -> Integer,
+        item_id -> Integer,
+        name -> Text,
+        color -> Nullable<Text>,
+        description -> Nullable<Text>,
+    }
+}
+
+table! {
+    item_reviews (id) {
+        id -> Integer,
+        item_id -> Integer,
+        reviewer -> Text,
+        state -> Text,
+        submitted_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    item_timeline (id) {
+        id -> Integer,
+        item_id -> Integer,
+        kind -> Text,
+        actor -> Text,
+        happened_at -> Timestamp,
+        detail -> Nullable<Text>,
+    }
+}
+
+table! {
+    last_commits (repo_id) {
+        repo_id -> Integer,
+        branch -> Text,
+        sha -> Text,
+        headline -> Text,
+        author -> Text,
+        committed_at -> Timestamp,
+        ci_status -> Nullable<Text>,
+    }
+}
+
 table! {
     repos (id) {
         id -> Integer,
         title -> Text,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        foreign_id -> Nullable<Text>,
+        renamed_from -> Nullable<Text>,
+        renamed_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    repo_events (id) {
+        id -> Integer,
+        repo_id -> Integer,
+        change -> Text,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+        recorded_at -> Timestamp,
     }
 }
 
@@ -16,14 +102,47 @@ table! {
         title -> Text,
         by -> Text,
         link -> Text,
-        labels -> Text,
         kind -> Text,
         last_updated -> Timestamp,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        state -> Text,
+        closed_at -> Nullable<Timestamp>,
+        closed_by -> Nullable<Text>,
+        by_url -> Nullable<Text>,
+        by_avatar -> Nullable<Text>,
+        by_kind -> Text,
+        labels_truncated -> Bool,
+        review_decision -> Nullable<Text>,
+        ci_status -> Nullable<Text>,
+        release_tag -> Nullable<Text>,
+        release_prerelease -> Bool,
+        release_published_at -> Nullable<Timestamp>,
+        discussion_category -> Nullable<Text>,
+        discussion_answered -> Bool,
+        discussion_comments -> Nullable<Integer>,
     }
 }
 
+joinable!(branch_health -> repos (repo_id));
+joinable!(item_events -> repos (repo_id));
+joinable!(item_failing_checks -> tracked_items (item_id));
+joinable!(item_labels -> tracked_items (item_id));
+joinable!(item_reviews -> tracked_items (item_id));
+joinable!(item_timeline -> tracked_items (item_id));
+joinable!(last_commits -> repos (repo_id));
+joinable!(repo_events -> repos (repo_id));
 joinable!(tracked_items -> repos (repo_id));
 
-allow_tables_to_appear_in_same_query!(repos, tracked_items,);
+allow_tables_to_appear_in_same_query!(
+    branch_health,
+    item_events,
+    item_failing_checks,
+    item_labels,
+    item_reviews,
+    item_timeline,
+    last_commits,
+    repo_events,
+    repos,
+    tracked_items,
+);
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 
@@ -5,11 +6,19 @@ use anyhow::{bail, Context, Result};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use diesel::sqlite::SqliteConnection;
 
-use crate::domain::{Author, ItemKind, Label, NewTrackedItem, State};
+use crate::domain::{
+    Author, AuthorKind, BranchHealth, Change, Checks, Commit, Discussion, ItemDetails, ItemEvent,
+    ItemKind, Label, NewTrackedItem, Release, RemoteRepo, RepoAlreadyExists, RepoChange, RepoEvent,
+    Review, Reviews, State, TimelineEvent,
+};
 
-use super::schema::{repos, tracked_items};
+use super::schema::{
+    branch_health, item_events, item_failing_checks, item_labels, item_reviews, item_timeline,
+    last_commits, repo_events, repos, tracked_items,
+};
 use super::{Db, FullStoredRepo, NewRepo, StoredRepo};
 
 type SqlitePool = Pool<ConnectionManager<SqliteConnection>>;
@@ -57,43 +66,145 @@ impl Db for SqliteDB {
                     title: &i.title,
                     link: &i.link,
                     by: &i.by.name,
-                    labels: &Label::join(&i.labels),
-                    kind: i.kind.to_string(),
+                    kind: i.kind().to_string(),
                     foreign_id: &i.foreign_id,
                     number: i.number,
                     last_updated: i.last_updated.naive_utc(),
+                    state: i.state.to_string(),
+                    closed_at: i.closed_at.map(|at| at.naive_utc()),
+                    closed_by: i.closed_by.as_ref().map(|author| author.name.as_str()),
+                    by_url: i.by.link.as_deref(),
+                    by_avatar: i.by.avatar.as_deref(),
+                    by_kind: i.by.kind.to_string(),
+                    labels_truncated: i.labels_truncated,
+                    review_decision: i
+                        .details
+                        .reviews()
+                        .and_then(|r| r.decision.as_ref())
+                        .map(|d| d.to_string()),
+                    ci_status: i
+                        .details
+                        .checks()
+                        .and_then(|c| c.status.as_ref())
+                        .map(|s| s.to_string()),
+                    release_tag: i.details.release().map(|r| r.tag.as_str()),
+                    release_prerelease: i.details.release().is_some_and(|r| r.prerelease),
+                    release_published_at: i.details.release().map(|r| r.published_at.naive_utc()),
+                    discussion_category: i.details.discussion().map(|d| d.category.as_str()),
+                    discussion_answered: i.details.discussion().is_some_and(|d| d.answered),
+                    discussion_comments: i.details.discussion().map(|d| d.comments),
                 };
 
-                diesel::insert_into(tracked_items::table)
-                    .values(&item)
-                    .execute(&conn)?;
+                // Tracking an item twice only refreshes what we know about it
+                let updated = diesel::update(
+                    tracked_items::table
+                        .filter(tracked_items::repo_id.eq(repo.id))
+                        .filter(tracked_items::foreign_id.eq(&i.foreign_id)),
+                )
+                .set(&item)
+                .execute(&conn)?;
+
+                if updated == 0 {
+                    diesel::insert_into(tracked_items::table)
+                        .values(&item)
+                        .execute(&conn)?;
+                }
+
+                let item_id = tracked_items::table
+                    .filter(tracked_items::repo_id.eq(repo.id))
+                    .filter(tracked_items::foreign_id.eq(&i.foreign_id))
+                    .select(tracked_items::id)
+                    .first(&conn)?;
+
+                replace_labels(&conn, &[item_id], &i.labels)?;
+                replace_reviews(&conn, &[item_id], i.details.reviews())?;
+                replace_failing_checks(&conn, &[item_id], i.details.checks())?;
+                replace_timeline(&conn, &[item_id], &i.timeline)?;
             }
 
             Result::Ok(())
         })
     }
 
-    fn update_tracked_item(&self, _repo: &StoredRepo, item: NewTrackedItem) -> Result<()> {
+    fn update_tracked_item(&self, repo: &StoredRepo, item: NewTrackedItem) -> Result<()> {
         use super::schema::tracked_items::dsl::*;
+        let conn = self.conn.get()?;
 
-        diesel::update(tracked_items.filter(foreign_id.eq(item.foreign_id)))
+        conn.transaction::<_, anyhow::Error, _>(|| {
+            // The same item can be tracked in another repo, which keeps its own copy
+            diesel::update(
+                tracked_items
+                    .filter(repo_id.eq(repo.id))
+                    .filter(foreign_id.eq(&item.foreign_id)),
+            )
             .set((
                 last_updated.eq(item.last_updated.naive_utc()),
-                labels.eq(Label::join(&item.labels)),
                 title.eq(item.title.clone()),
+                state.eq(item.state.to_string()),
+                closed_at.eq(item.closed_at.map(|at| at.naive_utc())),
+                closed_by.eq(item.closed_by.as_ref().map(|author| author.name.clone())),
+                by_url.eq(item.by.link.clone()),
+                by_avatar.eq(item.by.avatar.clone()),
+                by_kind.eq(item.by.kind.to_string()),
+                labels_truncated.eq(item.labels_truncated),
+                review_decision.eq(item
+                    .details
+                    .reviews()
+                    .and_then(|r| r.decision.as_ref())
+                    .map(|d| d.to_string())),
+                ci_status.eq(item
+                    .details
+                    .checks()
+                    .and_then(|c| c.status.as_ref())
+                    .map(|s| s.to_string())),
+                release_tag.eq(item.details.release().map(|r| r.tag.clone())),
+                release_prerelease.eq(item.details.release().is_some_and(|r| r.prerelease)),
+                release_published_at.eq(item.details.release().map(|r| r.published_at.naive_utc())),
+                discussion_category.eq(item.details.discussion().map(|d| d.category.clone())),
+                discussion_answered.eq(item.details.discussion().is_some_and(|d| d.answered)),
+                discussion_comments.eq(item.details.discussion().map(|d| d.comments)),
             ))
-            .execute(&self.conn.get().unwrap())
-            .map(|_affected| ())
-            .context(format!("failed to update item {}", item.title))
+            .execute(&conn)?;
+
+            let ids: Vec<i32> = tracked_items
+                .filter(repo_id.eq(repo.id))
+                .filter(foreign_id.eq(&item.foreign_id))
+                .select(id)
+                .load(&conn)?;
+
+            replace_labels(&conn, &ids, &item.labels)?;
+            replace_reviews(&conn, &ids, item.details.reviews())?;
+            replace_failing_checks(&conn, &ids, item.details.checks())?;
+            replace_timeline(&conn, &ids, &item.timeline)?;
+
+            Result::Ok(())
+        })
+        .context(format!("failed to update item {}", item.title))
     }
 
-    fn remove_tracked_item(&self, _repo: &StoredRepo, item: NewTrackedItem) -> Result<()> {
+    fn remove_tracked_item(&self, repo: &StoredRepo, item: NewTrackedItem) -> Result<()> {
         use super::schema::tracked_items::dsl::*;
+        let conn = self.conn.get()?;
 
-        diesel::delete(tracked_items.filter(foreign_id.eq(item.foreign_id)))
-            .execute(&self.conn.get().unwrap())
-            .map(|_affected| ())
-            .context(format!("failed to delete item {}", item.title))
+        conn.transaction::<_, anyhow::Error, _>(|| {
+            let ids: Vec<i32> = tracked_items
+                .filter(repo_id.eq(repo.id))
+                .filter(foreign_id.eq(&item.foreign_id))
+                .select(id)
+                .load(&conn)?;
+
+            forget_details(&conn, &ids)?;
+
+            diesel::delete(
+                tracked_items
+                    .filter(repo_id.eq(repo.id))
+                    .filter(foreign_id.eq(&item.foreign_id)),
+            )
+            .execute(&conn)?;
+
+            Result::Ok(())
+        })
+        .context(format!("failed to delete item {}", item.title))
     }
 
     fn all(&self) -> Result<Vec<FullStoredRepo>> {
@@ -105,36 +216,194 @@ impl Db for SqliteDB {
 
         let ids: Vec<i32> = rs.iter().map(|r| r.id).collect();
 
-        let items: Vec<Vec<RawTrackedItem>> = tracked_items::table
+        let raw_items: Vec<RawTrackedItem> = tracked_items::table
             .filter(tracked_items::columns::repo_id.eq_any(ids))
             .load(&conn)
-            .context("loading tracked items")?
-            .grouped_by(&rs[..]);
+            .context("loading tracked items")?;
+
+        let labels: Vec<Vec<RawLabel>> = RawLabel::belonging_to(&raw_items)
+            .order(item_labels::id.asc())
+            .load(&conn)
+            .context("loading labels")?
+            .grouped_by(&raw_items);
+
+        let mut labels_by_item = raw_items
+            .iter()
+            .map(|item| item.id)
+            .zip(labels)
+            .collect::<HashMap<_, _>>();
+
+        let raw_reviews: Vec<Vec<RawReview>> = RawReview::belonging_to(&raw_items)
+            .order(item_reviews::id.asc())
+            .load(&conn)
+            .context("loading reviews")?
+            .grouped_by(&raw_items);
+
+        let mut reviews_by_item = raw_items
+            .iter()
+            .map(|item| item.id)
+            .zip(raw_reviews)
+            .collect::<HashMap<_, _>>();
+
+        let failing_checks: Vec<Vec<RawFailingCheck>> = RawFailingCheck::belonging_to(&raw_items)
+            .order(item_failing_checks::id.asc())
+            .load(&conn)
+            .context("loading failing checks")?
+            .grouped_by(&raw_items);
+
+        let mut failing_checks_by_item = raw_items
+            .iter()
+            .map(|item| item.id)
+            .zip(failing_checks)
+            .collect::<HashMap<_, _>>();
+
+        let timelines: Vec<Vec<RawTimelineEvent>> = RawTimelineEvent::belonging_to(&raw_items)
+            .order(item_timeline::id.asc())
+            .load(&conn)
+            .context("loading timelines")?
+            .grouped_by(&raw_items);
+
+        let mut timeline_by_item = raw_items
+            .iter()
+            .map(|item| item.id)
+            .zip(timelines)
+            .collect::<HashMap<_, _>>();
+
+        let mut last_commit_by_repo = last_commits::table
+            .filter(last_commits::repo_id.eq_any(rs.iter().map(|r| r.id)))
+            .load::<RawLastCommit>(&conn)
+            .context("loading last commits")?
+            .into_iter()
+            .map(|commit| (commit.repo_id, commit))
+            .collect::<HashMap<_, _>>();
+
+        let mut health_by_repo = branch_health::table
+            .filter(branch_health::repo_id.eq_any(rs.iter().map(|r| r.id)))
+            .load::<RawBranchHealth>(&conn)
+            .context("loading the health of default branches")?
+            .into_iter()
+            .map(|health| (health.repo_id, health))
+            .collect::<HashMap<_, _>>();
+
+        let items: Vec<Vec<RawTrackedItem>> = raw_items.grouped_by(&rs[..]);
 
         Result::Ok(
             rs.into_iter()
                 .zip(items)
                 .map(|(repo, tracked)| {
-                    let (prs, issues) = tracked
+                    let (releases, items): (Vec<_>, Vec<_>) = tracked
                         .iter()
-                        .map(|item| NewTrackedItem {
-                            state: State::Open, // TODO: Need to derive this, or should it be assumed to be always open?
-                            title: item.title.clone(),
-                            by: Author::from(item.by.clone()),
-                            number: item.number,
-                            link: item.link.clone(),
-                            labels: Label::split(&item.labels),
-                            kind: ItemKind::from(item.kind.clone()),
-                            foreign_id: item.foreign_id.clone(),
-                            last_updated: DateTime::from_utc(item.last_updated, Utc),
+                        .filter_map(|item| {
+                            // A release or discussion missing what sets it apart is left out
+                            let details = match ItemKind::from(item.kind.clone()) {
+                                ItemKind::PR => ItemDetails::PR {
+                                    reviews: reviews(
+                                        item.review_decision.as_deref(),
+                                        reviews_by_item.remove(&item.id).unwrap_or_default(),
+                                    ),
+                                    checks: Checks {
+                                        status: item
+                                            .ci_status
+                                            .as_ref()
+                                            .and_then(|s| s.parse().ok()),
+                                        failing: failing_checks_by_item
+                                            .remove(&item.id)
+                                            .unwrap_or_default()
+                                            .into_iter()
+                                            .map(|check| check.name)
+                                            .collect(),
+                                    },
+                                },
+                                ItemKind::Issue => ItemDetails::Issue,
+                                ItemKind::Release => ItemDetails::Release(Release {
+                                    tag: item.release_tag.clone()?,
+                                    prerelease: item.release_prerelease,
+                                    published_at: DateTime::from_utc(
+                                        item.release_published_at?,
+                                        Utc,
+                                    ),
+                                }),
+                                ItemKind::Discussion => ItemDetails::Discussion(Discussion {
+                                    category: item.discussion_category.clone()?,
+                                    answered: item.discussion_answered,
+                                    comments: item.discussion_comments.unwrap_or_default(),
+                                }),
+                            };
+
+                            Some(NewTrackedItem {
+                                state: State::from(item.state.as_str()),
+                                title: item.title.clone(),
+                                by: Author {
+                                    name: item.by.clone(),
+                                    link: item.by_url.clone(),
+                                    avatar: item.by_avatar.clone(),
+                                    kind: AuthorKind::from(item.by_kind.as_str()),
+                                },
+                                number: item.number,
+                                link: item.link.clone(),
+                                labels: labels_by_item
+                                    .remove(&item.id)
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .map(|label| Label {
+                                        name: label.name,
+                                        color: label.color,
+                                        description: label.description,
+                                    })
+                                    .collect(),
+                                labels_truncated: item.labels_truncated,
+                                timeline: timeline_by_item
+                                    .remove(&item.id)
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .filter_map(|event| {
+                                        Some(TimelineEvent {
+                                            kind: event.kind.parse().ok()?,
+                                            by: event.actor,
+                                            at: DateTime::from_utc(event.happened_at, Utc),
+                                            detail: event.detail,
+                                        })
+                                    })
+                                    .collect(),
+                                details,
+                                foreign_id: item.foreign_id.clone(),
+                                last_updated: DateTime::from_utc(item.last_updated, Utc),
+                                closed_at: item.closed_at.map(|at| DateTime::from_utc(at, Utc)),
+                                closed_by: item.closed_by.clone().map(Author::from),
+                            })
                         })
-                        .partition(|item| item.kind == ItemKind::PR);
+                        .partition(|item| item.kind() == ItemKind::Release);
+                    let (discussions, items): (Vec<_>, Vec<_>) = items
+                        .into_iter()
+                        .partition(|item| item.kind() == ItemKind::Discussion);
+                    let (prs, issues) = items
+                        .into_iter()
+                        .partition(|item| item.kind() == ItemKind::PR);
 
                     FullStoredRepo {
                         id: repo.id,
                         title: repo.title,
+                        foreign_id: repo.foreign_id,
+                        renamed_from: repo.renamed_from,
+                        renamed_at: repo.renamed_at,
+                        last_commit: last_commit_by_repo.remove(&repo.id).map(|commit| Commit {
+                            branch: commit.branch,
+                            sha: commit.sha,
+                            headline: commit.headline,
+                            author: commit.author,
+                            committed_at: DateTime::from_utc(commit.committed_at, Utc),
+                            ci_status: commit.ci_status.and_then(|s| s.parse().ok()),
+                        }),
+                        branch_health: health_by_repo.remove(&repo.id).and_then(|health| {
+                            Some(BranchHealth {
+                                status: health.status.parse().ok()?,
+                                since: health.changed_at.map(|at| DateTime::from_utc(at, Utc)),
+                            })
+                        }),
                         prs,
                         issues,
+                        releases,
+                        discussions,
                     }
                 })
                 .collect(),
@@ -144,44 +413,480 @@ impl Db for SqliteDB {
     fn insert_new_repo(&self, repo_name: &str) -> Result<StoredRepo> {
         let conn = self.conn.get()?;
 
+        insert_repo(
+            &conn,
+            NewRepo {
+                title: repo_name,
+                foreign_id: None,
+            },
+        )
+    }
+
+    fn insert_remote_repo(&self, remote: &RemoteRepo) -> Result<StoredRepo> {
+        let conn = self.conn.get()?;
+        let name = remote.name.to_string();
+
+        insert_repo(
+            &conn,
+            NewRepo {
+                title: &name,
+                foreign_id: Some(&remote.foreign_id),
+            },
+        )
+    }
+
+    fn set_repo_foreign_id(&self, repo: &StoredRepo, repo_foreign_id: &str) -> Result<()> {
         use super::schema::repos::dsl::*;
-        let new_repo = NewRepo { title: repo_name };
+
+        diesel::update(repos.filter(id.eq(repo.id)))
+            .set(foreign_id.eq(repo_foreign_id))
+            .execute(&self.conn.get()?)
+            .map(|_affected| ())
+            .with_context(|| format!("failed to set the foreign id of {}", repo.title))
+    }
+
+    fn rename_repo(&self, repo: &StoredRepo, new_title: &str) -> Result<StoredRepo> {
+        let conn = self.conn.get()?;
+
+        use super::schema::repos::dsl::*;
+
+        let renamed = diesel::update(repos.filter(id.eq(repo.id)))
+            .set((
+                title.eq(new_title),
+                renamed_from.eq(&repo.title),
+                renamed_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(&conn);
+
+        match renamed {
+            Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                return Err(RepoAlreadyExists(new_title.to_string()).into())
+            }
+            Ok(0) => bail!("{} not found", repo.title),
+            renamed => {
+                renamed.with_context(|| format!("failed to rename {}", repo.title))?;
+            }
+        };
+
+        repos
+            .filter(id.eq(repo.id))
+            .first::<StoredRepo>(&conn)
+            .with_context(|| "retrieving renamed repo")
+    }
+
+    fn record_events(&self, repo: &StoredRepo, events: Vec<ItemEvent>) -> Result<()> {
+        let conn = self.conn.get()?;
 
         conn.transaction::<_, anyhow::Error, _>(|| {
-            diesel::insert_into(repos)
-                .values(&new_repo)
-                .execute(&conn)
-                .with_context(|| format!("failed to insert '{}'", repo_name))?;
+            for event in events.iter() {
+                let (change, old_value, new_value) = event.change.to_parts();
 
-            // this is kinda meh, but there is no 'RETURNING'
-            repos
-                .order(id.desc())
-                .first::<StoredRepo>(&conn)
-                .with_context(|| "retrieving stored repo")
+                diesel::insert_into(item_events::table)
+                    .values(&InsertableItemEvent {
+                        repo_id: repo.id,
+                        foreign_id: &event.foreign_id,
+                        number: event.number,
+                        change,
+                        old_value,
+                        new_value,
+                        recorded_at: event.recorded_at.naive_utc(),
+                    })
+                    .execute(&conn)
+                    .with_context(|| format!("failed to record a change of {}", event.number))?;
+            }
+
+            Result::Ok(())
         })
     }
 
-    fn delete(&self, repo: StoredRepo) -> Result<()> {
+    fn history(&self, repo: &StoredRepo, item_foreign_id: &str) -> Result<Vec<ItemEvent>> {
+        use super::schema::item_events::dsl::*;
+
+        let raw: Vec<RawItemEvent> = item_events
+            .filter(repo_id.eq(repo.id))
+            .filter(foreign_id.eq(item_foreign_id))
+            .order(id.asc())
+            .select((
+                foreign_id,
+                number,
+                change,
+                old_value,
+                new_value,
+                recorded_at,
+            ))
+            .load(&self.conn.get()?)
+            .with_context(|| format!("loading the history of {}", item_foreign_id))?;
+
+        raw.into_iter()
+            .map(|event| {
+                Ok(ItemEvent {
+                    foreign_id: event.foreign_id,
+                    number: event.number,
+                    change: Change::from_parts(&event.change, event.old_value, event.new_value)?,
+                    recorded_at: DateTime::from_utc(event.recorded_at, Utc),
+                })
+            })
+            .collect()
+    }
+
+    fn item_foreign_id(&self, repo: &StoredRepo, nr: i32) -> Result<Option<String>> {
         let conn = self.conn.get()?;
 
-        match diesel::delete(repos::table.filter(repos::id.eq(repo.id))).execute(&conn) {
-            Ok(size) if size == 1 => {}
-            Ok(_) => bail!("{} not found", repo.title),
-            Err(m) => bail!("could not delete repo: {}", m),
+        let tracked: Option<String> = tracked_items::table
+            .filter(tracked_items::repo_id.eq(repo.id))
+            .filter(tracked_items::number.eq(nr))
+            .filter(tracked_items::kind.ne(ItemKind::Release.to_string()))
+            .select(tracked_items::foreign_id)
+            .first(&conn)
+            .optional()?;
+
+        if tracked.is_some() {
+            return Ok(tracked);
+        }
+
+        // Untracked and resolved items only left their history behind
+        item_events::table
+            .filter(item_events::repo_id.eq(repo.id))
+            .filter(item_events::number.eq(nr))
+            .filter(item_events::change.ne_all(vec!["released", "prereleased"]))
+            .order(item_events::id.desc())
+            .select(item_events::foreign_id)
+            .first(&conn)
+            .optional()
+            .with_context(|| format!("looking up item {} of {}", nr, repo.title))
+    }
+
+    fn set_last_commit(&self, repo: &StoredRepo, commit: Commit) -> Result<()> {
+        let row = RawLastCommit {
+            repo_id: repo.id,
+            branch: commit.branch,
+            sha: commit.sha,
+            headline: commit.headline,
+            author: commit.author,
+            committed_at: commit.committed_at.naive_utc(),
+            ci_status: commit.ci_status.map(|s| s.to_string()),
         };
 
-        match diesel::delete(tracked_items::table.filter(tracked_items::repo_id.eq(repo.id)))
-            .execute(&conn)
-        {
-            Ok(_) => {}
-            Err(m) => bail!("could not delete tracked for repo repo: {}", m),
+        diesel::replace_into(last_commits::table)
+            .values(&row)
+            .execute(&self.conn.get()?)
+            .map(|_| ())
+            .with_context(|| format!("setting the last commit of {}", repo.title))
+    }
+
+    fn set_branch_health(&self, repo: &StoredRepo, health: BranchHealth) -> Result<()> {
+        let row = RawBranchHealth {
+            repo_id: repo.id,
+            status: health.status.to_string(),
+            changed_at: health.since.map(|since| since.naive_utc()),
         };
 
-        Ok(())
+        diesel::replace_into(branch_health::table)
+            .values(&row)
+            .execute(&self.conn.get()?)
+            .map(|_| ())
+            .with_context(|| format!("setting the branch health of {}", repo.title))
+    }
+
+    fn record_repo_events(&self, repo: &StoredRepo, events: Vec<RepoEvent>) -> Result<()> {
+        let conn = self.conn.get()?;
+
+        conn.transaction::<_, anyhow::Error, _>(|| {
+            for event in events.iter() {
+                let (change, old_value, new_value) = event.change.to_parts();
+
+                diesel::insert_into(repo_events::table)
+                    .values(&InsertableRepoEvent {
+                        repo_id: repo.id,
+                        change,
+                        old_value,
+                        new_value,
+                        recorded_at: event.recorded_at.naive_utc(),
+                    })
+                    .execute(&conn)
+                    .with_context(|| format!("failed to record a change of {}", repo.title))?;
+            }
+
+            Result::Ok(())
+        })
+    }
+
+    fn repo_history(&self, repo: &StoredRepo) -> Result<Vec<RepoEvent>> {
+        use super::schema::repo_events::dsl::*;
+
+        let raw: Vec<RawRepoEvent> = repo_events
+            .filter(repo_id.eq(repo.id))
+            .order(id.asc())
+            .select((change, old_value, new_value, recorded_at))
+            .load(&self.conn.get()?)
+            .with_context(|| format!("loading the history of {}", repo.title))?;
+
+        raw.into_iter()
+            .map(|event| {
+                Ok(RepoEvent {
+                    change: RepoChange::from_parts(
+                        &event.change,
+                        event.old_value,
+                        event.new_value,
+                    )?,
+                    recorded_at: DateTime::from_utc(event.recorded_at, Utc),
+                })
+            })
+            .collect()
+    }
+
+    fn delete(&self, repo: StoredRepo) -> Result<()> {
+        let conn = self.conn.get()?;
+
+        // A repo is either gone with everything that belongs to it, or still there
+        conn.transaction::<_, anyhow::Error, _>(|| {
+            match diesel::delete(repos::table.filter(repos::id.eq(repo.id))).execute(&conn) {
+                Ok(1) => {}
+                Ok(_) => bail!("{} not found", repo.title),
+                Err(m) => bail!("could not delete repo: {}", m),
+            };
+
+            match tracked_items::table
+                .filter(tracked_items::repo_id.eq(repo.id))
+                .select(tracked_items::id)
+                .load::<i32>(&conn)
+                .and_then(|ids| forget_details(&conn, &ids))
+            {
+                Ok(_) => {}
+                Err(m) => bail!("could not delete the details of tracked items: {}", m),
+            };
+
+            match diesel::delete(tracked_items::table.filter(tracked_items::repo_id.eq(repo.id)))
+                .execute(&conn)
+            {
+                Ok(_) => {}
+                Err(m) => bail!("could not delete tracked for repo repo: {}", m),
+            };
+
+            match diesel::delete(item_events::table.filter(item_events::repo_id.eq(repo.id)))
+                .execute(&conn)
+            {
+                Ok(_) => {}
+                Err(m) => bail!("could not delete the history of repo: {}", m),
+            };
+
+            match diesel::delete(last_commits::table.filter(last_commits::repo_id.eq(repo.id)))
+                .execute(&conn)
+            {
+                Ok(_) => {}
+                Err(m) => bail!("could not delete the last commit of repo: {}", m),
+            };
+
+            match diesel::delete(branch_health::table.filter(branch_health::repo_id.eq(repo.id)))
+                .execute(&conn)
+            {
+                Ok(_) => {}
+                Err(m) => bail!("could not delete the branch health of repo: {}", m),
+            };
+
+            match diesel::delete(repo_events::table.filter(repo_events::repo_id.eq(repo.id)))
+                .execute(&conn)
+            {
+                Ok(_) => {}
+                Err(m) => bail!("could not delete the changes of repo: {}", m),
+            };
+
+            Ok(())
+        })
     }
 }
 
-#[derive(Insertable)]
+/// Drops the labels, reviews, failing checks and timelines of items that are about to go.
+/// Adds the repo, telling a repo that is already there apart from other failures.
+fn insert_repo(conn: &SqliteConnection, new_repo: NewRepo) -> Result<StoredRepo> {
+    use super::schema::repos::dsl::*;
+
+    conn.transaction::<_, anyhow::Error, _>(|| {
+        match diesel::insert_into(repos).values(&new_repo).execute(conn) {
+            Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                return Err(RepoAlreadyExists(new_repo.title.to_string()).into())
+            }
+            inserted => {
+                inserted.with_context(|| format!("failed to insert '{}'", new_repo.title))?;
+            }
+        };
+
+        // this is kinda meh, but there is no 'RETURNING'
+        repos
+            .order(id.desc())
+            .first::<StoredRepo>(conn)
+            .with_context(|| "retrieving stored repo")
+    })
+}
+
+fn forget_details(conn: &SqliteConnection, item_ids: &[i32]) -> QueryResult<()> {
+    diesel::delete(item_labels::table.filter(item_labels::item_id.eq_any(item_ids)))
+        .execute(conn)?;
+    diesel::delete(item_reviews::table.filter(item_reviews::item_id.eq_any(item_ids)))
+        .execute(conn)?;
+    diesel::delete(
+        item_failing_checks::table.filter(item_failing_checks::item_id.eq_any(item_ids)),
+    )
+    .execute(conn)?;
+    diesel::delete(item_timeline::table.filter(item_timeline::item_id.eq_any(item_ids)))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Labels are replaced as a whole, GitHub tells us about all of them every time.
+fn replace_labels(conn: &SqliteConnection, item_ids: &[i32], labels: &[Label]) -> QueryResult<()> {
+    diesel::delete(item_labels::table.filter(item_labels::item_id.eq_any(item_ids)))
+        .execute(conn)?;
+
+    for item_id in item_ids {
+        let rows = labels
+            .iter()
+            .map(|label| InsertableLabel {
+                item_id: *item_id,
+                name: &label.name,
+                color: label.color.as_deref(),
+                description: label.description.as_deref(),
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(item_labels::table)
+            .values(&rows)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+/// Outstanding review requests are stored as reviews in the `requested` state.
+const REQUESTED: &str = "requested";
+
+/// Like labels, reviews are replaced as a whole.
+fn replace_reviews(
+    conn: &SqliteConnection,
+    item_ids: &[i32],
+    reviews: Option<&Reviews>,
+) -> QueryResult<()> {
+    diesel::delete(item_reviews::table.filter(item_reviews::item_id.eq_any(item_ids)))
+        .execute(conn)?;
+
+    // Only PRs are reviewed
+    let reviews = match reviews {
+        Some(reviews) => reviews,
+        None => return Ok(()),
+    };
+
+    for item_id in item_ids {
+        let requested = reviews.requested.iter().map(|reviewer| InsertableReview {
+            item_id: *item_id,
+            reviewer,
+            state: REQUESTED.to_string(),
+            submitted_at: None,
+        });
+        let latest = reviews.latest.iter().map(|review| InsertableReview {
+            item_id: *item_id,
+            reviewer: &review.by,
+            state: review.state.to_string(),
+            submitted_at: review.submitted_at.map(|at| at.naive_utc()),
+        });
+        let rows = requested.chain(latest).collect::<Vec<_>>();
+
+        diesel::insert_into(item_reviews::table)
+            .values(&rows)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+/// Checks only tell us what failed on the latest commit, so they are replaced as a whole too.
+fn replace_failing_checks(
+    conn: &SqliteConnection,
+    item_ids: &[i32],
+    checks: Option<&Checks>,
+) -> QueryResult<()> {
+    diesel::delete(
+        item_failing_checks::table.filter(item_failing_checks::item_id.eq_any(item_ids)),
+    )
+    .execute(conn)?;
+
+    let checks = match checks {
+        Some(checks) => checks,
+        None => return Ok(()),
+    };
+
+    for item_id in item_ids {
+        let rows = checks
+            .failing
+            .iter()
+            .map(|name| InsertableFailingCheck {
+                item_id: *item_id,
+                name,
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(item_failing_checks::table)
+            .values(&rows)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+/// GitHub only hands out the latest few events, so the timeline is replaced as a whole as well.
+fn replace_timeline(
+    conn: &SqliteConnection,
+    item_ids: &[i32],
+    timeline: &[TimelineEvent],
+) -> QueryResult<()> {
+    diesel::delete(item_timeline::table.filter(item_timeline::item_id.eq_any(item_ids)))
+        .execute(conn)?;
+
+    for item_id in item_ids {
+        let rows = timeline
+            .iter()
+            .map(|event| InsertableTimelineEvent {
+                item_id: *item_id,
+                kind: event.kind.to_string(),
+                actor: &event.by,
+                happened_at: event.at.naive_utc(),
+                detail: event.detail.as_deref(),
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(item_timeline::table)
+            .values(&rows)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+fn reviews(decision: Option<&str>, raw: Vec<RawReview>) -> Reviews {
+    let (requested, latest): (Vec<_>, Vec<_>) = raw
+        .into_iter()
+        .partition(|review| review.state == REQUESTED);
+
+    Reviews {
+        decision: decision.and_then(|decision| decision.parse().ok()),
+        requested: requested
+            .into_iter()
+            .map(|review| review.reviewer)
+            .collect(),
+        latest: latest
+            .into_iter()
+            .filter_map(|review| {
+                Some(Review {
+                    state: review.state.parse().ok()?,
+                    by: review.reviewer,
+                    submitted_at: review.submitted_at.map(|at| DateTime::from_utc(at, Utc)),
+                })
+            })
+            .collect(),
+    }
+}
+
+#[derive(Insertable, AsChangeset)]
 #[table_name = "tracked_items"]
 struct InsertableTrackedItem<'a> {
     repo_id: i32,
@@ -190,9 +895,161 @@ struct InsertableTrackedItem<'a> {
     title: &'a str,
     link: &'a str,
     by: &'a str,
-    labels: &'a str,
     kind: String,
     last_updated: NaiveDateTime,
+    state: String,
+    closed_at: Option<NaiveDateTime>,
+    closed_by: Option<&'a str>,
+    by_url: Option<&'a str>,
+    by_avatar: Option<&'a str>,
+    by_kind: String,
+    labels_truncated: bool,
+    review_decision: Option<String>,
+    ci_status: Option<String>,
+    release_tag: Option<&'a str>,
+    release_prerelease: bool,
+    release_published_at: Option<NaiveDateTime>,
+    discussion_category: Option<&'a str>,
+    discussion_answered: bool,
+    discussion_comments: Option<i32>,
+}
+
+#[derive(Insertable, Queryable)]
+#[table_name = "last_commits"]
+struct RawLastCommit {
+    repo_id: i32,
+    branch: String,
+    sha: String,
+    headline: String,
+    author: String,
+    committed_at: NaiveDateTime,
+    ci_status: Option<String>,
+}
+
+#[derive(Insertable, Queryable)]
+#[table_name = "branch_health"]
+struct RawBranchHealth {
+    repo_id: i32,
+    status: String,
+    changed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[table_name = "item_events"]
+struct InsertableItemEvent<'a> {
+    repo_id: i32,
+    foreign_id: &'a str,
+    number: i32,
+    change: &'a str,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    recorded_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Debug)]
+struct RawItemEvent {
+    foreign_id: String,
+    number: i32,
+    change: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    recorded_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "repo_events"]
+struct InsertableRepoEvent<'a> {
+    repo_id: i32,
+    change: &'a str,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    recorded_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Debug)]
+struct RawRepoEvent {
+    change: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    recorded_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "item_labels"]
+struct InsertableLabel<'a> {
+    item_id: i32,
+    name: &'a str,
+    color: Option<&'a str>,
+    description: Option<&'a str>,
+}
+
+#[derive(Insertable)]
+#[table_name = "item_reviews"]
+struct InsertableReview<'a> {
+    item_id: i32,
+    reviewer: &'a str,
+    state: String,
+    submitted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Associations, Identifiable, Queryable, Debug)]
+#[belongs_to(RawTrackedItem, foreign_key = "item_id")]
+#[table_name = "item_reviews"]
+struct RawReview {
+    id: i32,
+    item_id: i32,
+    reviewer: String,
+    state: String,
+    submitted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[table_name = "item_timeline"]
+struct InsertableTimelineEvent<'a> {
+    item_id: i32,
+    kind: String,
+    actor: &'a str,
+    happened_at: NaiveDateTime,
+    detail: Option<&'a str>,
+}
+
+#[derive(Associations, Identifiable, Queryable, Debug)]
+#[belongs_to(RawTrackedItem, foreign_key = "item_id")]
+#[table_name = "item_timeline"]
+struct RawTimelineEvent {
+    id: i32,
+    item_id: i32,
+    kind: String,
+    actor: String,
+    happened_at: NaiveDateTime,
+    detail: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "item_failing_checks"]
+struct InsertableFailingCheck<'a> {
+    item_id: i32,
+    name: &'a str,
+}
+
+#[derive(Associations, Identifiable, Queryable, Debug)]
+#[belongs_to(RawTrackedItem, foreign_key = "item_id")]
+#[table_name = "item_failing_checks"]
+struct RawFailingCheck {
+    id: i32,
+    item_id: i32,
+    name: String,
+}
+
+#[derive(Associations, Identifiable, Queryable, Debug)]
+#[belongs_to(RawTrackedItem, foreign_key = "item_id")]
+#[table_name = "item_labels"]
+struct RawLabel {
+    id: i32,
+    item_id: i32,
+    name: String,
+    color: Option<String>,
+    description: Option<String>,
 }
 
 #[derive(Associations, Identifiable, Queryable, Debug)]
@@ -206,11 +1063,25 @@ struct RawTrackedItem {
     title: String,
     by: String,
     link: String,
-    labels: String,
     kind: String,
     last_updated: NaiveDateTime,
     created_at: NaiveDateTime,
     updated_at: NaiveDateTime,
+    state: String,
+    closed_at: Option<NaiveDateTime>,
+    closed_by: Option<String>,
+    by_url: Option<String>,
+    by_avatar: Option<String>,
+    by_kind: String,
+    labels_truncated: bool,
+    review_decision: Option<String>,
+    ci_status: Option<String>,
+    release_tag: Option<String>,
+    release_prerelease: bool,
+    release_published_at: Option<NaiveDateTime>,
+    discussion_category: Option<String>,
+    discussion_answered: bool,
+    discussion_comments: Option<i32>,
 }
 
 #[cfg(test)]
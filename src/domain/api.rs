@@ -1,15 +1,16 @@
-use serde::{Deserialize, Serialize};
-use std::convert::From;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::convert::{From, TryFrom};
 
 use crate::db::FullStoredRepo;
-use crate::domain::{ItemKind, Label, NewTrackedItem};
+use crate::domain::{self, ItemKind, NewTrackedItem, TrackingOutcome};
 
 #[derive(serde::Deserialize, Debug)]
 pub struct AddNewRepo {
     pub name: String,
 }
 
-#[derive(serde::Deserialize, Debug)]
+#[derive(serde::Deserialize, Debug, Clone)]
 pub struct ItemToTrack {
     pub kind: ItemKind,
     pub nr: i32,
@@ -20,6 +21,32 @@ pub struct AddTrackedItemsForRepo {
     pub items: Vec<ItemToTrack>,
 }
 
+impl From<Vec<(ItemToTrack, TrackingOutcome)>> for TrackingReport {
+    fn from(outcomes: Vec<(ItemToTrack, TrackingOutcome)>) -> Self {
+        TrackingReport {
+            items: outcomes
+                .into_iter()
+                .map(|(item, outcome)| {
+                    let (outcome, reason) = match outcome {
+                        TrackingOutcome::Tracked => ("tracked", None),
+                        TrackingOutcome::AlreadyTracked => ("already_tracked", None),
+                        TrackingOutcome::NotFound => ("not_found", None),
+                        TrackingOutcome::WrongKind => ("wrong_kind", None),
+                        TrackingOutcome::Failed(reason) => ("failed", Some(reason)),
+                    };
+
+                    TrackingResult {
+                        kind: item.kind.to_string(),
+                        nr: item.nr,
+                        outcome: outcome.to_string(),
+                        reason,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
 impl From<FullStoredRepo> for Repo {
     fn from(other: FullStoredRepo) -> Self {
         let FullStoredRepo {
@@ -27,30 +54,335 @@ impl From<FullStoredRepo> for Repo {
             title,
             issues,
             prs,
+            mut releases,
+            discussions,
+            renamed_from,
+            renamed_at,
+            last_commit,
+            branch_health,
             ..
         } = other;
 
+        let renamed = match (renamed_from, renamed_at) {
+            (Some(from), Some(at)) => Some(Rename {
+                from,
+                at: DateTime::<Utc>::from_utc(at, Utc).to_rfc3339(),
+            }),
+            _ => None,
+        };
+
+        let (mut resolved, open): (Vec<_>, Vec<_>) = issues
+            .into_iter()
+            .chain(prs)
+            .chain(discussions)
+            .partition(|item| item.state.is_resolved());
+        resolved.sort_by_key(|item| std::cmp::Reverse(item.closed_at));
+
+        let (discussions, open): (Vec<_>, Vec<_>) = open
+            .into_iter()
+            .partition(|item| item.kind() == ItemKind::Discussion);
+        let (prs, issues): (Vec<_>, Vec<_>) = open
+            .into_iter()
+            .partition(|item| item.kind() == ItemKind::PR);
+
+        releases.sort_by_key(|item| {
+            std::cmp::Reverse(item.details.release().map(|release| release.published_at))
+        });
+
         Repo {
             id,
             title,
+            renamed,
+            last_commit: last_commit.map(Commit::from),
+            branch_health: branch_health.map(BranchHealth::from),
             activity: Activity {
                 issues: issues.into_iter().map(Item::from).collect(),
                 prs: prs.into_iter().map(Item::from).collect(),
+                resolved: resolved.into_iter().map(Item::from).collect(),
+                releases: releases.into_iter().map(Item::from).collect(),
+                discussions: discussions.into_iter().map(Item::from).collect(),
             },
         }
     }
 }
 
+impl From<domain::Commit> for Commit {
+    fn from(other: domain::Commit) -> Self {
+        Commit {
+            branch: other.branch,
+            sha: other.sha,
+            headline: other.headline,
+            author: other.author,
+            committed_at: other.committed_at.to_rfc3339(),
+            ci_status: other.ci_status.map(|status| status.to_string()),
+        }
+    }
+}
+
+impl TryFrom<Commit> for domain::Commit {
+    type Error = anyhow::Error;
+
+    fn try_from(other: Commit) -> anyhow::Result<Self> {
+        Ok(domain::Commit {
+            branch: other.branch,
+            sha: other.sha,
+            headline: other.headline,
+            author: other.author,
+            committed_at: DateTime::parse_from_rfc3339(&other.committed_at)?.with_timezone(&Utc),
+            ci_status: other.ci_status.and_then(|status| status.parse().ok()),
+        })
+    }
+}
+
+impl From<domain::BranchHealth> for BranchHealth {
+    fn from(other: domain::BranchHealth) -> Self {
+        BranchHealth {
+            state: other.status.to_string(),
+            since: other.since.map(|since| since.to_rfc3339()),
+        }
+    }
+}
+
+impl TryFrom<BranchHealth> for domain::BranchHealth {
+    type Error = anyhow::Error;
+
+    fn try_from(other: BranchHealth) -> anyhow::Result<Self> {
+        Ok(domain::BranchHealth {
+            status: other.state.parse()?,
+            since: other
+                .since
+                .map(|since| DateTime::parse_from_rfc3339(&since))
+                .transpose()?
+                .map(|since| since.with_timezone(&Utc)),
+        })
+    }
+}
+
 impl From<NewTrackedItem> for Item {
     fn from(other: NewTrackedItem) -> Self {
+        let kind = other.kind().to_string();
+        let (reviews, checks, release, discussion) = match other.details {
+            domain::ItemDetails::PR {
+                reviews,
+                checks: domain::Checks { status, failing },
+            } => (
+                Some(Reviews::from(reviews)),
+                status.map(|status| Checks {
+                    state: status.to_string(),
+                    failing,
+                }),
+                None,
+                None,
+            ),
+            domain::ItemDetails::Issue => (None, None, None, None),
+            domain::ItemDetails::Release(release) => {
+                (None, None, Some(Release::from(release)), None)
+            }
+            domain::ItemDetails::Discussion(discussion) => {
+                (None, None, None, Some(Discussion::from(discussion)))
+            }
+        };
+
         Item {
             nr: other.number,
-            kind: other.kind.to_string(),
+            kind,
+            state: other.state.to_string(),
             title: other.title,
             link: other.link,
-            by: other.by.name,
-            labels: Label::expose(&other.labels),
+            by: Author::from(other.by),
+            labels: other.labels.into_iter().map(Label::from).collect(),
+            labels_truncated: other.labels_truncated,
+            reviews,
+            checks,
+            timeline: other
+                .timeline
+                .into_iter()
+                .map(TimelineEvent::from)
+                .collect(),
+            release,
+            discussion,
             last_updated: other.last_updated.to_rfc3339(),
+            closed_at: other.closed_at.map(|at| at.to_rfc3339()),
+            closed_by: other.closed_by.map(|author| author.name),
+        }
+    }
+}
+
+impl From<domain::Author> for Author {
+    fn from(other: domain::Author) -> Self {
+        Author {
+            login: other.name,
+            url: other.link,
+            avatar: other.avatar,
+            kind: other.kind.to_string(),
+        }
+    }
+}
+
+impl From<Author> for domain::Author {
+    fn from(other: Author) -> Self {
+        domain::Author {
+            name: other.login,
+            link: other.url,
+            avatar: other.avatar,
+            kind: domain::AuthorKind::from(other.kind.as_str()),
+        }
+    }
+}
+
+impl From<domain::Label> for Label {
+    fn from(other: domain::Label) -> Self {
+        Label {
+            name: other.name,
+            color: other.color,
+            description: other.description,
+        }
+    }
+}
+
+impl From<Label> for domain::Label {
+    fn from(other: Label) -> Self {
+        domain::Label {
+            name: other.name,
+            color: other.color,
+            description: other.description,
+        }
+    }
+}
+
+impl From<domain::Reviews> for Reviews {
+    fn from(other: domain::Reviews) -> Self {
+        Reviews {
+            decision: other.decision.map(|decision| decision.to_string()),
+            requested: other.requested,
+            latest: other
+                .latest
+                .into_iter()
+                .map(|review| Review {
+                    by: review.by,
+                    state: review.state.to_string(),
+                    submitted_at: review.submitted_at.map(|at| at.to_rfc3339()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Reviews> for domain::Reviews {
+    fn from(other: Reviews) -> Self {
+        domain::Reviews {
+            decision: other.decision.and_then(|decision| decision.parse().ok()),
+            requested: other.requested,
+            latest: other
+                .latest
+                .into_iter()
+                .filter_map(|review| {
+                    Some(domain::Review {
+                        by: review.by,
+                        state: review.state.parse().ok()?,
+                        submitted_at: review.submitted_at.and_then(|at| {
+                            DateTime::parse_from_rfc3339(&at)
+                                .ok()
+                                .map(|at| at.with_timezone(&Utc))
+                        }),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Checks> for domain::Checks {
+    fn from(other: Checks) -> Self {
+        domain::Checks {
+            status: other.state.parse().ok(),
+            failing: other.failing,
+        }
+    }
+}
+
+impl From<domain::Release> for Release {
+    fn from(other: domain::Release) -> Self {
+        Release {
+            tag: other.tag,
+            prerelease: other.prerelease,
+            published_at: other.published_at.to_rfc3339(),
+        }
+    }
+}
+
+impl TryFrom<Release> for domain::Release {
+    type Error = anyhow::Error;
+
+    fn try_from(other: Release) -> anyhow::Result<Self> {
+        Ok(domain::Release {
+            tag: other.tag,
+            prerelease: other.prerelease,
+            published_at: DateTime::parse_from_rfc3339(&other.published_at)?.with_timezone(&Utc),
+        })
+    }
+}
+
+impl From<domain::Discussion> for Discussion {
+    fn from(other: domain::Discussion) -> Self {
+        Discussion {
+            category: other.category,
+            answered: other.answered,
+            comments: other.comments,
+        }
+    }
+}
+
+impl From<Discussion> for domain::Discussion {
+    fn from(other: Discussion) -> Self {
+        domain::Discussion {
+            category: other.category,
+            answered: other.answered,
+            comments: other.comments,
+        }
+    }
+}
+
+impl From<domain::TimelineEvent> for TimelineEvent {
+    fn from(other: domain::TimelineEvent) -> Self {
+        TimelineEvent {
+            kind: other.kind.to_string(),
+            by: other.by,
+            at: other.at.to_rfc3339(),
+            detail: other.detail,
+        }
+    }
+}
+
+impl TryFrom<TimelineEvent> for domain::TimelineEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(other: TimelineEvent) -> anyhow::Result<Self> {
+        Ok(domain::TimelineEvent {
+            kind: other.kind.parse()?,
+            by: other.by,
+            at: DateTime::parse_from_rfc3339(&other.at)?.with_timezone(&Utc),
+            detail: other.detail,
+        })
+    }
+}
+
+impl From<domain::LiveItems> for LiveItems {
+    fn from(other: domain::LiveItems) -> Self {
+        LiveItems {
+            items: other.items.into_iter().map(Item::from).collect(),
+            truncated: other.truncated,
+        }
+    }
+}
+
+impl From<domain::RateLimit> for RateLimit {
+    fn from(other: domain::RateLimit) -> Self {
+        RateLimit {
+            limit: other.limit,
+            cost: other.cost,
+            remaining: other.remaining,
+            reset_at: other.reset_at.to_rfc3339(),
         }
     }
 }
@@ -60,23 +392,272 @@ impl From<NewTrackedItem> for Item {
 pub struct Item {
     pub nr: i32,
     pub kind: String,
+    /// Older JSON stores did not keep the state of items around.
+    #[serde(default)]
+    pub state: String,
     pub title: String,
     pub link: String,
-    pub by: String,
-    pub labels: Vec<String>,
+    /// Older JSON stores only kept the login of the author.
+    #[serde(deserialize_with = "author_or_login")]
+    pub by: Author,
+    /// Older JSON stores only kept the names of labels.
+    #[serde(deserialize_with = "labels_or_names")]
+    pub labels: Vec<Label>,
+    /// Set when the item has more labels than are listed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub labels_truncated: bool,
+    /// Only PRs are reviewed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviews: Option<Reviews>,
+    /// Only PRs with something running on their head commit have checks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checks: Option<Checks>,
+    /// The latest comments, closes and cross-references, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeline: Vec<TimelineEvent>,
+    /// Only releases have a tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<Release>,
+    /// Only discussions have a category.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discussion: Option<Discussion>,
     pub last_updated: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_by: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Author {
+    pub login: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+    /// One of `user`, `bot` or `organization`.
+    #[serde(default)]
+    pub kind: String,
+}
+
+fn author_or_login<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Author, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Login(String),
+        Author(Author),
+    }
+
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Login(login) => Author::from(domain::Author::new(login)),
+        Stored::Author(author) => author,
+    })
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Label {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+fn labels_or_names<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Label>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Name(String),
+        Label(Label),
+    }
+
+    let labels = Vec::<Stored>::deserialize(deserializer)?
+        .into_iter()
+        .map(|stored| match stored {
+            Stored::Name(name) => Label::from(domain::Label::new(name)),
+            Stored::Label(label) => label,
+        })
+        .collect();
+
+    Ok(labels)
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Reviews {
+    /// One of `approved`, `changes_requested` or `review_required`, if the repo requires reviews.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<String>,
+    pub requested: Vec<String>,
+    pub latest: Vec<Review>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Review {
+    pub by: String,
+    pub state: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Checks {
+    /// One of `passing`, `failing` or `pending`.
+    pub state: String,
+    pub failing: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Release {
+    pub tag: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prerelease: bool,
+    pub published_at: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Discussion {
+    pub category: String,
+    pub answered: bool,
+    pub comments: i32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TimelineEvent {
+    /// One of `commented`, `closed` or `cross_referenced`.
+    pub kind: String,
+    pub by: String,
+    pub at: String,
+    /// The start of a comment, or the issue or PR that referenced the item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ItemEvent {
+    pub change: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub recorded_at: String,
+}
+
+/// Something that happened to a repo as a whole, like its default branch turning red.
+#[derive(Serialize, Debug)]
+pub struct RepoEvent {
+    pub change: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub recorded_at: String,
+}
+
+impl From<domain::RepoEvent> for RepoEvent {
+    fn from(other: domain::RepoEvent) -> Self {
+        let (change, from, to) = other.change.to_parts();
+
+        RepoEvent {
+            change: change.to_string(),
+            from,
+            to,
+            recorded_at: other.recorded_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<domain::ItemEvent> for ItemEvent {
+    fn from(other: domain::ItemEvent) -> Self {
+        let (change, from, to) = other.change.to_parts();
+
+        ItemEvent {
+            change: change.to_string(),
+            from,
+            to,
+            recorded_at: other.recorded_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TrackingReport {
+    pub items: Vec<TrackingResult>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TrackingResult {
+    pub kind: String,
+    pub nr: i32,
+    pub outcome: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LiveItems {
+    pub items: Vec<Item>,
+    pub truncated: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RateLimit {
+    pub limit: i64,
+    pub cost: i64,
+    pub remaining: i64,
+    pub reset_at: String,
 }
 
 #[derive(Serialize, Debug)]
 pub struct Activity {
     pub prs: Vec<Item>,
     pub issues: Vec<Item>,
+    /// Recently closed or merged issues and PRs, most recent first.
+    pub resolved: Vec<Item>,
+    /// The latest releases, newest first.
+    pub releases: Vec<Item>,
+    pub discussions: Vec<Item>,
+}
+
+/// The most recent time a repo was renamed or transferred on GitHub.
+#[derive(Serialize, Debug)]
+pub struct Rename {
+    pub from: String,
+    pub at: String,
+}
+
+/// The latest commit on the default branch of a repo.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Commit {
+    pub branch: String,
+    pub sha: String,
+    pub headline: String,
+    pub author: String,
+    pub committed_at: String,
+    /// One of `passing`, `failing` or `pending`, if anything runs on the commit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ci_status: Option<String>,
+}
+
+/// Whether the default branch of a repo is green, and since when.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BranchHealth {
+    /// Either `passing` or `failing`.
+    pub state: String,
+    /// Unset until the branch was seen turning red or green.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
 }
 
 #[derive(Serialize, Debug)]
 pub struct Repo {
     pub id: i32,
     pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed: Option<Rename>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit: Option<Commit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_health: Option<BranchHealth>,
     pub activity: Activity,
 }
 
@@ -84,6 +665,7 @@ pub struct Repo {
 mod test {
     use super::*;
     use assert_json_diff::assert_json_eq;
+    use chrono::TimeZone;
     use serde_json::json;
 
     #[test]
@@ -121,29 +703,347 @@ mod test {
         assert_eq!(add.items.len(), 2)
     }
 
+    #[test]
+    fn serialize_item_events_json() {
+        use chrono::{TimeZone, Utc};
+
+        let events = vec![
+            ItemEvent::from(domain::ItemEvent {
+                foreign_id: "node-1".into(),
+                number: 1,
+                change: domain::Change::LabelAdded("bug".into()),
+                recorded_at: Utc.ymd(2020, 2, 25).and_hms(20, 0, 0),
+            }),
+            ItemEvent::from(domain::ItemEvent {
+                foreign_id: "node-1".into(),
+                number: 1,
+                change: domain::Change::State {
+                    from: domain::State::Open,
+                    to: domain::State::Merged,
+                },
+                recorded_at: Utc.ymd(2020, 2, 25).and_hms(21, 0, 0),
+            }),
+        ];
+
+        let events_json = serde_json::to_value(&events).unwrap();
+
+        assert_json_eq!(
+            events_json,
+            json!([
+              {
+                "change": "label_added",
+                "to": "bug",
+                "recorded_at": "2020-02-25T20:00:00+00:00"
+              },
+              {
+                "change": "state",
+                "from": "open",
+                "to": "merged",
+                "recorded_at": "2020-02-25T21:00:00+00:00"
+              }
+            ])
+        )
+    }
+
+    #[test]
+    fn serialize_repo_events_json() {
+        use chrono::{TimeZone, Utc};
+
+        let events = vec![RepoEvent::from(domain::RepoEvent {
+            change: domain::RepoChange::BranchHealth {
+                from: domain::CiStatus::Passing,
+                to: domain::CiStatus::Failing,
+            },
+            recorded_at: Utc.ymd(2020, 3, 7).and_hms(9, 0, 0),
+        })];
+
+        let events_json = serde_json::to_value(&events).unwrap();
+
+        assert_json_eq!(
+            events_json,
+            json!([
+              {
+                "change": "branch_health",
+                "from": "passing",
+                "to": "failing",
+                "recorded_at": "2020-03-07T09:00:00+00:00"
+              }
+            ])
+        )
+    }
+
+    #[test]
+    fn serialize_a_renamed_repo_json() {
+        let repo = Repo::from(FullStoredRepo {
+            id: 42,
+            title: "new-owner/bar".into(),
+            foreign_id: Some("MDEwOlJlcG9zaXRvcnkx".into()),
+            renamed_from: Some("foo/bar".into()),
+            renamed_at: Some(chrono::NaiveDate::from_ymd(2020, 2, 22).and_hms(14, 0, 0)),
+            last_commit: None,
+            branch_health: None,
+            issues: vec![],
+            prs: vec![],
+            releases: vec![],
+            discussions: vec![],
+        });
+
+        let repo_json = serde_json::to_value(&repo).unwrap();
+
+        assert_json_eq!(
+            repo_json,
+            json!({
+              "id": 42,
+              "title": "new-owner/bar",
+              "renamed": {
+                "from": "foo/bar",
+                "at": "2020-02-22T14:00:00+00:00"
+              },
+              "activity": {
+                "prs": [],
+                "issues": [],
+                "resolved": [],
+                "releases": [],
+                "discussions": []
+              }
+            })
+        )
+    }
+
+    #[test]
+    fn serialize_the_last_commit_and_branch_health_of_a_repo_json() {
+        let repo = Repo::from(FullStoredRepo {
+            id: 42,
+            title: "foo/bar".into(),
+            foreign_id: None,
+            renamed_from: None,
+            renamed_at: None,
+            last_commit: Some(domain::Commit {
+                branch: "main".into(),
+                sha: "8a2f1e0".into(),
+                headline: "Speed up the build".into(),
+                author: "octocat".into(),
+                committed_at: Utc.ymd(2020, 3, 3).and_hms(8, 0, 0),
+                ci_status: Some(domain::CiStatus::Failing),
+            }),
+            branch_health: Some(domain::BranchHealth {
+                status: domain::CiStatus::Failing,
+                since: Some(Utc.ymd(2020, 3, 3).and_hms(8, 20, 0)),
+            }),
+            issues: vec![],
+            prs: vec![],
+            releases: vec![],
+            discussions: vec![],
+        });
+
+        let repo_json = serde_json::to_value(&repo).unwrap();
+
+        assert_json_eq!(
+            repo_json,
+            json!({
+              "id": 42,
+              "title": "foo/bar",
+              "last_commit": {
+                "branch": "main",
+                "sha": "8a2f1e0",
+                "headline": "Speed up the build",
+                "author": "octocat",
+                "committed_at": "2020-03-03T08:00:00+00:00",
+                "ci_status": "failing"
+              },
+              "branch_health": {
+                "state": "failing",
+                "since": "2020-03-03T08:20:00+00:00"
+              },
+              "activity": {
+                "prs": [],
+                "issues": [],
+                "resolved": [],
+                "releases": [],
+                "discussions": []
+              }
+            })
+        )
+    }
+
+    fn someone() -> Author {
+        Author::from(domain::Author::new("Someone"))
+    }
+
+    #[test]
+    fn reads_items_that_only_kept_the_login_of_their_author() {
+        let item: Item = serde_json::from_value(json!({
+          "nr": 10,
+          "kind": "issue",
+          "title": "Important",
+          "link": "https://example.com/1",
+          "by": "Someone",
+          "last_updated": "2019-09-18T01:24:29+00:00",
+          "labels": []
+        }))
+        .unwrap();
+
+        assert_eq!(item.by.login, "Someone");
+        assert_eq!(item.by.kind, "user");
+        assert_eq!(item.by.url, None);
+    }
+
+    #[test]
+    fn reads_items_that_only_kept_the_names_of_their_labels() {
+        let item: Item = serde_json::from_value(json!({
+          "nr": 10,
+          "kind": "issue",
+          "title": "Important",
+          "link": "https://example.com/1",
+          "by": "Someone",
+          "last_updated": "2019-09-18T01:24:29+00:00",
+          "labels": ["bug", "help wanted"]
+        }))
+        .unwrap();
+
+        let names = item
+            .labels
+            .iter()
+            .map(|l| l.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["bug", "help wanted"]);
+        assert!(item.labels[0].color.is_none());
+    }
+
     #[test]
     fn serialize_an_entire_repo_json() {
         let repo = Repo {
             id: 42,
             title: "foo/bar".into(),
+            renamed: None,
+            last_commit: None,
+            branch_health: None,
             activity: Activity {
                 prs: vec![Item {
                     kind: ItemKind::PR.to_string(),
+                    state: "draft".into(),
                     nr: 1,
                     title: "Fix important build failure".into(),
                     link: "https://example.com/1".into(),
-                    by: "Someone".into(),
-                    labels: vec!["foo".to_string(), "bar".to_string()],
+                    by: Author {
+                        login: "dependabot".into(),
+                        url: Some("https://github.com/apps/dependabot".into()),
+                        avatar: Some("https://avatars.example.com/dependabot".into()),
+                        kind: "bot".into(),
+                    },
+                    labels: vec![
+                        Label {
+                            name: "foo".into(),
+                            color: Some("d73a4a".into()),
+                            description: Some("Something isn't working".into()),
+                        },
+                        Label::from(domain::Label::new("bar, baz".into())),
+                    ],
+                    labels_truncated: true,
+                    reviews: Some(Reviews {
+                        decision: Some("changes_requested".into()),
+                        requested: vec!["octocat".into()],
+                        latest: vec![Review {
+                            by: "hubot".into(),
+                            state: "changes_requested".into(),
+                            submitted_at: Some("2019-09-18T01:20:00+00:00".into()),
+                        }],
+                    }),
+                    checks: Some(Checks {
+                        state: "failing".into(),
+                        failing: vec!["build".into()],
+                    }),
+                    timeline: vec![TimelineEvent {
+                        kind: "commented".into(),
+                        by: "hubot".into(),
+                        at: "2019-09-18T01:22:00+00:00".into(),
+                        detail: Some("Looks good to me".into()),
+                    }],
+                    release: None,
+                    discussion: None,
                     last_updated: "2019-09-18T01:24:29+00:00".to_string(),
+                    closed_at: None,
+                    closed_by: None,
                 }],
                 issues: vec![Item {
                     kind: ItemKind::Issue.to_string(),
+                    state: "open".into(),
                     nr: 10,
                     title: "Important".into(),
                     link: "https://example.com/1".into(),
-                    by: "Someone".into(),
-                    labels: vec!["foo".to_string()],
+                    by: someone(),
+                    labels: vec![Label::from(domain::Label::new("foo".into()))],
+                    labels_truncated: false,
+                    reviews: None,
+                    checks: None,
+                    timeline: vec![],
+                    release: None,
+                    discussion: None,
+                    last_updated: "2019-09-18T01:24:29+00:00".to_string(),
+                    closed_at: None,
+                    closed_by: None,
+                }],
+                resolved: vec![Item {
+                    kind: ItemKind::PR.to_string(),
+                    state: "merged".into(),
+                    nr: 3,
+                    title: "Speed things up".into(),
+                    link: "https://example.com/3".into(),
+                    by: someone(),
+                    labels: vec![],
+                    labels_truncated: false,
+                    reviews: None,
+                    checks: None,
+                    timeline: vec![],
+                    release: None,
+                    discussion: None,
                     last_updated: "2019-09-18T01:24:29+00:00".to_string(),
+                    closed_at: Some("2019-09-18T01:24:29+00:00".to_string()),
+                    closed_by: Some("Someone Else".into()),
+                }],
+                releases: vec![Item {
+                    kind: ItemKind::Release.to_string(),
+                    state: "open".into(),
+                    nr: 1234,
+                    title: "Version 2.0 beta".into(),
+                    link: "https://example.com/releases/v2.0.0-beta.1".into(),
+                    by: someone(),
+                    labels: vec![],
+                    labels_truncated: false,
+                    reviews: None,
+                    checks: None,
+                    timeline: vec![],
+                    release: Some(Release {
+                        tag: "v2.0.0-beta.1".into(),
+                        prerelease: true,
+                        published_at: "2019-09-17T12:00:00+00:00".into(),
+                    }),
+                    discussion: None,
+                    last_updated: "2019-09-17T12:00:00+00:00".to_string(),
+                    closed_at: None,
+                    closed_by: None,
+                }],
+                discussions: vec![Item {
+                    kind: ItemKind::Discussion.to_string(),
+                    state: "open".into(),
+                    nr: 77,
+                    title: "How do I configure this?".into(),
+                    link: "https://example.com/discussions/77".into(),
+                    by: someone(),
+                    labels: vec![],
+                    labels_truncated: false,
+                    reviews: None,
+                    checks: None,
+                    timeline: vec![],
+                    release: None,
+                    discussion: Some(Discussion {
+                        category: "Q&A".into(),
+                        answered: true,
+                        comments: 4,
+                    }),
+                    last_updated: "2019-09-16T12:00:00+00:00".to_string(),
+                    closed_at: None,
+                    closed_by: None,
                 }],
             },
         };
@@ -160,13 +1060,47 @@ mod test {
                   {
                     "nr": 1,
                     "kind": "pr",
+                    "state": "draft",
                     "title": "Fix important build failure",
                     "link": "https://example.com/1",
-                    "by": "Someone",
+                    "by": {
+                      "login": "dependabot",
+                      "url": "https://github.com/apps/dependabot",
+                      "avatar": "https://avatars.example.com/dependabot",
+                      "kind": "bot"
+                    },
                     "last_updated": "2019-09-18T01:24:29+00:00",
                     "labels": [
-                      "foo",
-                      "bar"
+                      {
+                        "name": "foo",
+                        "color": "d73a4a",
+                        "description": "Something isn't working"
+                      },
+                      { "name": "bar, baz" }
+                    ],
+                    "labels_truncated": true,
+                    "reviews": {
+                      "decision": "changes_requested",
+                      "requested": ["octocat"],
+                      "latest": [
+                        {
+                          "by": "hubot",
+                          "state": "changes_requested",
+                          "submitted_at": "2019-09-18T01:20:00+00:00"
+                        }
+                      ]
+                    },
+                    "checks": {
+                      "state": "failing",
+                      "failing": ["build"]
+                    },
+                    "timeline": [
+                      {
+                        "kind": "commented",
+                        "by": "hubot",
+                        "at": "2019-09-18T01:22:00+00:00",
+                        "detail": "Looks good to me"
+                      }
                     ]
                   }
                 ],
@@ -174,17 +1108,112 @@ mod test {
                   {
                     "nr": 10,
                     "kind": "issue",
+                    "state": "open",
                     "title": "Important",
                     "link": "https://example.com/1",
-                    "by": "Someone",
+                    "by": { "login": "Someone", "kind": "user" },
                     "last_updated": "2019-09-18T01:24:29+00:00",
                     "labels": [
-                      "foo"
+                      { "name": "foo" }
                     ]
                   }
+                ],
+                "resolved": [
+                  {
+                    "nr": 3,
+                    "kind": "pr",
+                    "state": "merged",
+                    "title": "Speed things up",
+                    "link": "https://example.com/3",
+                    "by": { "login": "Someone", "kind": "user" },
+                    "last_updated": "2019-09-18T01:24:29+00:00",
+                    "labels": [],
+                    "closed_at": "2019-09-18T01:24:29+00:00",
+                    "closed_by": "Someone Else"
+                  }
+                ],
+                "releases": [
+                  {
+                    "nr": 1234,
+                    "kind": "release",
+                    "state": "open",
+                    "title": "Version 2.0 beta",
+                    "link": "https://example.com/releases/v2.0.0-beta.1",
+                    "by": { "login": "Someone", "kind": "user" },
+                    "last_updated": "2019-09-17T12:00:00+00:00",
+                    "labels": [],
+                    "release": {
+                      "tag": "v2.0.0-beta.1",
+                      "prerelease": true,
+                      "published_at": "2019-09-17T12:00:00+00:00"
+                    }
+                  }
+                ],
+                "discussions": [
+                  {
+                    "nr": 77,
+                    "kind": "discussion",
+                    "state": "open",
+                    "title": "How do I configure this?",
+                    "link": "https://example.com/discussions/77",
+                    "by": { "login": "Someone", "kind": "user" },
+                    "last_updated": "2019-09-16T12:00:00+00:00",
+                    "labels": [],
+                    "discussion": {
+                      "category": "Q&A",
+                      "answered": true,
+                      "comments": 4
+                    }
+                  }
                 ]
               }
             })
         );
     }
+
+    #[test]
+    fn serialize_live_items_json() {
+        let live = LiveItems {
+            items: vec![Item {
+                kind: ItemKind::Issue.to_string(),
+                state: "open".into(),
+                nr: 10,
+                title: "Important".into(),
+                link: "https://example.com/1".into(),
+                by: someone(),
+                labels: vec![],
+                labels_truncated: false,
+                reviews: None,
+                checks: None,
+                timeline: vec![],
+                release: None,
+                discussion: None,
+                last_updated: "2019-09-18T01:24:29+00:00".to_string(),
+                closed_at: None,
+                closed_by: None,
+            }],
+            truncated: true,
+        };
+
+        let live_json = serde_json::to_value(&live).unwrap();
+
+        assert_json_eq!(
+            live_json,
+            json!({
+              "items": [
+                {
+                  "nr": 10,
+                  "kind": "issue",
+                  "state": "open",
+                  "title": "Important",
+                  "link": "https://example.com/1",
+                  "by": { "login": "Someone", "kind": "user" },
+                  "last_updated": "2019-09-18T01:24:29+00:00",
+                  "labels": []
+                }
+              ],
+              "truncated": true
+            })
+        );
+    }
 }
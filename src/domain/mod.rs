@@ -2,21 +2,164 @@ use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
 use anyhow::{bail, Result};
-use async_std::prelude::*;
-use async_std::task;
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use futures::stream::futures_unordered::FuturesUnordered;
+use futures::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
 use crate::db::{Db, StoredRepo};
+use crate::BetterOption;
 
 pub mod api;
 pub mod updater;
 
+#[async_trait]
 pub trait ClientForRepositories: Send + Sync {
-    fn repo_exists(&self, repo: &RepoName) -> Result<bool>;
-    fn entire_repo(&self, repo: &RepoName) -> Result<Vec<NewTrackedItem>>;
-    fn issue(&self, repo: &RepoName, nr: i32) -> Result<NewTrackedItem>;
-    fn pull_request(&self, repo: &RepoName, nr: i32) -> Result<NewTrackedItem>;
+    fn rate_limit(&self) -> Option<RateLimit>;
+    /// The repo as GitHub knows it, with its canonical `owner/name`, if it exists.
+    async fn repo_exists(&self, repo: &RepoName) -> Result<Option<RemoteRepo>>;
+    async fn entire_repo(&self, repo: &RepoName) -> Result<LiveItems>;
+    async fn issue(&self, repo: &RepoName, nr: i32) -> Result<NewTrackedItem>;
+    async fn pull_request(&self, repo: &RepoName, nr: i32) -> Result<NewTrackedItem>;
+    /// The latest comments, closes and cross-references of an issue or PR, oldest first.
+    async fn timeline(&self, repo: &RepoName, nr: i32) -> Result<Vec<TimelineEvent>>;
+    /// Fetches the current state of many issues and PRs at once, by their GitHub node IDs.
+    /// Items that no longer exist are left out.
+    async fn refresh_items(&self, foreign_ids: &[String]) -> Result<RefreshedItems>;
+    /// Fetches the current names of repos by their GitHub node IDs, to notice renames and transfers.
+    /// Repos that no longer exist or could not be looked up are left out.
+    async fn refresh_repos(&self, foreign_ids: &[String]) -> Result<Vec<RemoteRepo>>;
+    async fn discussion(&self, repo: &RepoName, nr: i32) -> Result<NewTrackedItem>;
+    /// The latest published releases of each repo, newest first, by the node ID of the repo.
+    /// Repos that no longer exist are left out.
+    async fn releases(
+        &self,
+        foreign_ids: &[String],
+    ) -> Result<HashMap<String, Vec<NewTrackedItem>>>;
+    /// The latest commit on the default branch of each repo, by the node ID of the repo.
+    /// Empty repos and repos that no longer exist are left out.
+    async fn last_commits(&self, foreign_ids: &[String]) -> Result<HashMap<String, Commit>>;
+}
+
+/// Why a request to GitHub failed for good, after any retries.
+#[derive(Debug)]
+pub enum ClientError {
+    /// Might work when tried again later, e.g. GitHub was down or throttled us.
+    Transient(String),
+    /// The repo, issue or PR does not exist, or is not visible to us.
+    NotFound(String),
+    /// Will keep failing, e.g. the query was rejected or the token is invalid.
+    Permanent(String),
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Transient(reason) => write!(f, "GitHub is unavailable: {}", reason),
+            ClientError::NotFound(reason) => write!(f, "not found on GitHub: {}", reason),
+            ClientError::Permanent(reason) => write!(f, "GitHub rejected the request: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// A repo with that name is already being tracked.
+#[derive(Debug)]
+pub struct RepoAlreadyExists(pub String);
+
+impl Display for RepoAlreadyExists {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is already being tracked", self.0)
+    }
+}
+
+impl std::error::Error for RepoAlreadyExists {}
+
+/// What was given as a repo is neither `owner/name` nor a GitHub URL of one.
+#[derive(Debug)]
+pub struct InvalidRepoName(pub String);
+
+impl Display for InvalidRepoName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not derive owner and name from repo: {}. Expected owner/name or a GitHub URL.",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidRepoName {}
+
+/// GitHub does not know a repo with that name, or does not let us see it.
+#[derive(Debug)]
+pub struct RepoNotFound(pub String);
+
+impl Display for RepoNotFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Repo {} not found on GitHub.", self.0)
+    }
+}
+
+impl std::error::Error for RepoNotFound {}
+
+/// Releases come and go along with their repo, so they can not be untracked one by one.
+#[derive(Debug)]
+pub struct ReleaseNotUntrackable(pub i32);
+
+impl Display for ReleaseNotUntrackable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "release {} is kept along with its repo and can not be untracked",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ReleaseNotUntrackable {}
+
+/// GitHub's GraphQL budget as of the most recent query.
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    pub limit: i64,
+    /// What the most recent query cost.
+    pub cost: i64,
+    pub remaining: i64,
+    pub reset_at: DateTime<Utc>,
+}
+
+/// A repo as it currently is on GitHub.
+#[derive(Clone, Debug)]
+pub struct RemoteRepo {
+    pub foreign_id: String,
+    pub name: RepoName,
+}
+
+/// The latest commit on the default branch of a repo.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Commit {
+    /// The default branch, as repos are free to pick its name.
+    pub branch: String,
+    pub sha: String,
+    pub headline: String,
+    /// The GitHub login of the author if GitHub knows them, their git name otherwise.
+    pub author: String,
+    pub committed_at: DateTime<Utc>,
+    /// Unset when nothing runs on the commit.
+    pub ci_status: Option<CiStatus>,
+}
+
+/// Whether the checks on the default branch of a repo pass, and since when.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BranchHealth {
+    /// Only ever passing or failing, a pending commit leaves the branch as it was.
+    pub status: CiStatus,
+    /// When the updater saw the branch turn red or green. Unset while the branch is as it
+    /// was first seen, as there is no telling how long it has been that way.
+    pub since: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Debug)]
@@ -26,12 +169,32 @@ pub struct RepoName {
 }
 
 impl RepoName {
+    /// Understands `owner/name` as well as the HTTPS and SSH URLs GitHub hands out for a repo.
     pub fn from<S: Into<String>>(input: S) -> Result<Self> {
         let t = input.into();
-        let parts = t.split('/').collect::<Vec<_>>();
+        let trimmed = t.trim();
+
+        let path = if let Some(scheme_end) = trimmed.find("://") {
+            // https://github.com/owner/name, ssh://git@github.com/owner/name
+            let after_scheme = &trimmed[scheme_end + 3..];
+            match after_scheme.find('/') {
+                Some(host_end) => &after_scheme[host_end + 1..],
+                None => return Err(InvalidRepoName(t).into()),
+            }
+        } else if let Some(colon) = trimmed.find(':') {
+            // git@github.com:owner/name
+            &trimmed[colon + 1..]
+        } else {
+            trimmed
+        };
+
+        let path = path.split(&['?', '#'][..]).next().unwrap_or("");
+        let path = path.trim_end_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
 
-        if parts.len() < 2 {
-            bail!("Could not derive owner and name from repo: {}", t);
+        let parts = path.split('/').collect::<Vec<_>>();
+        if parts.len() != 2 || !parts.iter().all(|part| is_valid_segment(part)) {
+            return Err(InvalidRepoName(t).into());
         }
 
         let owner = String::from(parts[0]);
@@ -41,18 +204,39 @@ impl RepoName {
     }
 }
 
+fn is_valid_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment != "."
+        && segment != ".."
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
 impl Display for RepoName {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}/{}", self.owner, self.name)
     }
 }
 
-#[derive(serde::Deserialize, Debug, Clone, Eq, PartialEq)]
+#[derive(serde::Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
 pub enum ItemKind {
     #[serde(rename = "pr")]
     PR,
     #[serde(rename = "issue")]
     Issue,
+    /// Picked up on their own for every tracked repo, rather than tracked one by one.
+    #[serde(rename = "release")]
+    Release,
+    #[serde(rename = "discussion")]
+    Discussion,
+}
+
+impl ItemKind {
+    /// Only issues and PRs have a timeline on GitHub.
+    pub fn has_timeline(&self) -> bool {
+        *self == ItemKind::Issue || *self == ItemKind::PR
+    }
 }
 
 impl From<String> for ItemKind {
@@ -63,16 +247,38 @@ impl From<String> for ItemKind {
         if s == "issue" {
             return ItemKind::Issue;
         }
+        if s == "release" {
+            return ItemKind::Release;
+        }
+        if s == "discussion" {
+            return ItemKind::Discussion;
+        }
         unreachable!()
     }
 }
 
+impl FromStr for ItemKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pr" => Ok(ItemKind::PR),
+            "issue" => Ok(ItemKind::Issue),
+            "release" => Ok(ItemKind::Release),
+            "discussion" => Ok(ItemKind::Discussion),
+            _ => bail!("{} is not a pr, an issue, a release or a discussion", s),
+        }
+    }
+}
+
 impl ToString for ItemKind {
     fn to_string(&self) -> String {
         use ItemKind::*;
         match self {
             PR => String::from("pr"),
             Issue => String::from("issue"),
+            Release => String::from("release"),
+            Discussion => String::from("discussion"),
         }
     }
 }
@@ -80,62 +286,516 @@ impl ToString for ItemKind {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum State {
     Open,
+    /// A PR that is open but not ready for review yet.
+    Draft,
+    /// A PR that was merged. Merged PRs are closed as well.
+    Merged,
     Closed,
 }
 
+impl State {
+    /// Whether nothing more is going to happen with the item.
+    pub fn is_resolved(&self) -> bool {
+        match self {
+            State::Merged | State::Closed => true,
+            State::Open | State::Draft => false,
+        }
+    }
+}
+
 impl Display for State {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let val = match self {
             State::Open => "open",
+            State::Draft => "draft",
+            State::Merged => "merged",
             State::Closed => "closed",
         };
         write!(f, "{}", val)
     }
 }
 
+impl From<&str> for State {
+    /// Items stored before their state was kept were all open.
+    fn from(s: &str) -> Self {
+        match s {
+            "draft" => State::Draft,
+            "merged" => State::Merged,
+            "closed" => State::Closed,
+            _ => State::Open,
+        }
+    }
+}
+
 // TODO: ths needs a better name
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct NewTrackedItem {
     pub title: String,
     pub state: State,
     pub link: String,
     pub by: Author,
     pub labels: Vec<Label>,
-    pub kind: ItemKind,
+    /// Set when the item has more labels than were fetched.
+    pub labels_truncated: bool,
+    /// What happened to the item lately, oldest first.
+    pub timeline: Vec<TimelineEvent>,
+    pub details: ItemDetails,
     pub foreign_id: String,
     pub last_updated: DateTime<Utc>,
     pub number: i32,
+    /// When the item was closed or merged, if it was.
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Who closed or merged the item, if GitHub knows.
+    pub closed_by: Option<Author>,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-pub struct Label(String);
+impl NewTrackedItem {
+    pub fn kind(&self) -> ItemKind {
+        self.details.kind()
+    }
+}
 
-impl Label {
-    pub fn new(name: String) -> Self {
-        Label(name)
+/// What only one kind of item has. The kind of an item follows from it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ItemDetails {
+    PR { reviews: Reviews, checks: Checks },
+    Issue,
+    Release(Release),
+    Discussion(Discussion),
+}
+
+impl ItemDetails {
+    pub fn kind(&self) -> ItemKind {
+        match self {
+            ItemDetails::PR { .. } => ItemKind::PR,
+            ItemDetails::Issue => ItemKind::Issue,
+            ItemDetails::Release(_) => ItemKind::Release,
+            ItemDetails::Discussion(_) => ItemKind::Discussion,
+        }
     }
 
-    pub fn join(labels: &[Label]) -> String {
-        labels
-            .iter()
-            .map(|l| l.0.clone())
-            .collect::<Vec<_>>()
-            .join(",")
+    pub fn reviews(&self) -> Option<&Reviews> {
+        match self {
+            ItemDetails::PR { reviews, .. } => Some(reviews),
+            _ => None,
+        }
+    }
+
+    pub fn checks(&self) -> Option<&Checks> {
+        match self {
+            ItemDetails::PR { checks, .. } => Some(checks),
+            _ => None,
+        }
+    }
+
+    pub fn release(&self) -> Option<&Release> {
+        match self {
+            ItemDetails::Release(release) => Some(release),
+            _ => None,
+        }
+    }
+
+    pub fn discussion(&self) -> Option<&Discussion> {
+        match self {
+            ItemDetails::Discussion(discussion) => Some(discussion),
+            _ => None,
+        }
+    }
+}
+
+/// Where a PR stands with its reviews. Issues are never reviewed.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Reviews {
+    /// Unset when the repo does not require reviews.
+    pub decision: Option<ReviewDecision>,
+    /// The users and teams that were asked for a review and have not given one yet.
+    pub requested: Vec<String>,
+    /// The most recent review of every reviewer.
+    pub latest: Vec<Review>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Review {
+    pub by: String,
+    pub state: ReviewState,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+impl Display for ReviewDecision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = match self {
+            ReviewDecision::Approved => "approved",
+            ReviewDecision::ChangesRequested => "changes_requested",
+            ReviewDecision::ReviewRequired => "review_required",
+        };
+        write!(f, "{}", val)
+    }
+}
+
+impl FromStr for ReviewDecision {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "approved" => Ok(ReviewDecision::Approved),
+            "changes_requested" => Ok(ReviewDecision::ChangesRequested),
+            "review_required" => Ok(ReviewDecision::ReviewRequired),
+            _ => bail!("{} is not a review decision", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ReviewState {
+    Approved,
+    ChangesRequested,
+    Commented,
+    Dismissed,
+    /// Started but not submitted yet.
+    Pending,
+}
+
+impl ReviewState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReviewState::Approved => "approved",
+            ReviewState::ChangesRequested => "changes_requested",
+            ReviewState::Commented => "commented",
+            ReviewState::Dismissed => "dismissed",
+            ReviewState::Pending => "pending",
+        }
+    }
+}
+
+impl Display for ReviewState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ReviewState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "approved" => Ok(ReviewState::Approved),
+            "changes_requested" => Ok(ReviewState::ChangesRequested),
+            "commented" => Ok(ReviewState::Commented),
+            "dismissed" => Ok(ReviewState::Dismissed),
+            "pending" => Ok(ReviewState::Pending),
+            _ => bail!("{} is not a review state", s),
+        }
+    }
+}
+
+/// How the checks on the head commit of a PR are doing. Issues have no checks.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Checks {
+    /// Unset when nothing ran on the head commit.
+    pub status: Option<CiStatus>,
+    /// The names of the checks and statuses that failed.
+    pub failing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CiStatus {
+    Passing,
+    Failing,
+    Pending,
+}
+
+impl Display for CiStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = match self {
+            CiStatus::Passing => "passing",
+            CiStatus::Failing => "failing",
+            CiStatus::Pending => "pending",
+        };
+        write!(f, "{}", val)
+    }
+}
+
+impl FromStr for CiStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "passing" => Ok(CiStatus::Passing),
+            "failing" => Ok(CiStatus::Failing),
+            "pending" => Ok(CiStatus::Pending),
+            _ => bail!("{} is not a CI status", s),
+        }
+    }
+}
+
+/// What sets a release apart from issues and PRs. Its name is the title of the item.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Release {
+    pub tag: String,
+    pub prerelease: bool,
+    pub published_at: DateTime<Utc>,
+}
+
+/// What sets a discussion apart from issues and PRs.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Discussion {
+    pub category: String,
+    /// Set once a comment was marked as the answer.
+    pub answered: bool,
+    pub comments: i32,
+}
+
+/// Something that happened to an issue or PR, as seen on its timeline on GitHub.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TimelineEvent {
+    pub kind: TimelineEventKind,
+    /// Who commented, closed or referenced the item.
+    pub by: String,
+    pub at: DateTime<Utc>,
+    /// The start of a comment, or the issue or PR that referenced the item.
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TimelineEventKind {
+    Commented,
+    Closed,
+    CrossReferenced,
+}
+
+impl Display for TimelineEventKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = match self {
+            TimelineEventKind::Commented => "commented",
+            TimelineEventKind::Closed => "closed",
+            TimelineEventKind::CrossReferenced => "cross_referenced",
+        };
+        write!(f, "{}", val)
     }
+}
+
+impl FromStr for TimelineEventKind {
+    type Err = anyhow::Error;
 
-    pub fn split(raw: &str) -> Vec<Label> {
-        if raw == "" {
-            return Vec::new();
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "commented" => Ok(TimelineEventKind::Commented),
+            "closed" => Ok(TimelineEventKind::Closed),
+            "cross_referenced" => Ok(TimelineEventKind::CrossReferenced),
+            _ => bail!("{} is not a timeline event", s),
+        }
+    }
+}
+
+/// Something about a tracked item that the updater saw change on GitHub.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Change {
+    Title {
+        from: String,
+        to: String,
+    },
+    LabelAdded(String),
+    LabelRemoved(String),
+    State {
+        from: State,
+        to: State,
+    },
+    Updated {
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    },
+    ReviewDecision {
+        from: Option<ReviewDecision>,
+        to: Option<ReviewDecision>,
+    },
+    ReviewRequested(String),
+    /// A review request was withdrawn before the reviewer got to it.
+    ReviewRequestRemoved(String),
+    /// Someone submitted a review.
+    Reviewed {
+        by: String,
+        state: ReviewState,
+    },
+    /// A new release was published, or a prerelease was promoted to a proper release.
+    Released(String),
+    Prereleased(String),
+    /// A comment on a discussion was marked as the answer.
+    Answered,
+    /// The checks on the head commit went e.g. from passing to failing.
+    Ci {
+        from: Option<CiStatus>,
+        to: Option<CiStatus>,
+    },
+}
+
+impl Change {
+    /// Releases are numbered by their ID rather than alongside issues, PRs and discussions.
+    pub fn is_release(&self) -> bool {
+        matches!(self, Change::Released(_) | Change::Prereleased(_))
+    }
+
+    /// The kind of change along with the values before and after, the way they are stored.
+    pub fn to_parts(&self) -> (&'static str, Option<String>, Option<String>) {
+        match self {
+            Change::Title { from, to } => ("title", Some(from.clone()), Some(to.clone())),
+            Change::LabelAdded(label) => ("label_added", None, Some(label.clone())),
+            Change::LabelRemoved(label) => ("label_removed", Some(label.clone()), None),
+            Change::State { from, to } => ("state", Some(from.to_string()), Some(to.to_string())),
+            Change::Updated { from, to } => {
+                ("updated", Some(from.to_rfc3339()), Some(to.to_rfc3339()))
+            }
+            Change::ReviewDecision { from, to } => (
+                "review_decision",
+                from.as_ref().map(ReviewDecision::to_string),
+                to.as_ref().map(ReviewDecision::to_string),
+            ),
+            Change::ReviewRequested(reviewer) => ("review_requested", None, Some(reviewer.clone())),
+            Change::ReviewRequestRemoved(reviewer) => {
+                ("review_request_removed", Some(reviewer.clone()), None)
+            }
+            Change::Reviewed { by, state } => {
+                ("reviewed", Some(state.to_string()), Some(by.clone()))
+            }
+            Change::Released(tag) => ("released", None, Some(tag.clone())),
+            Change::Prereleased(tag) => ("prereleased", None, Some(tag.clone())),
+            Change::Answered => ("answered", None, None),
+            Change::Ci { from, to } => (
+                "ci",
+                from.as_ref().map(CiStatus::to_string),
+                to.as_ref().map(CiStatus::to_string),
+            ),
+        }
+    }
+
+    pub fn from_parts(kind: &str, from: Option<String>, to: Option<String>) -> Result<Self> {
+        let change = match (kind, from, to) {
+            ("title", Some(from), Some(to)) => Change::Title { from, to },
+            ("label_added", None, Some(label)) => Change::LabelAdded(label),
+            ("label_removed", Some(label), None) => Change::LabelRemoved(label),
+            ("state", Some(from), Some(to)) => Change::State {
+                from: State::from(from.as_str()),
+                to: State::from(to.as_str()),
+            },
+            ("updated", Some(from), Some(to)) => Change::Updated {
+                from: DateTime::parse_from_rfc3339(&from)?.with_timezone(&Utc),
+                to: DateTime::parse_from_rfc3339(&to)?.with_timezone(&Utc),
+            },
+            ("review_decision", from, to) => Change::ReviewDecision {
+                from: from.map(|from| from.parse()).transpose()?,
+                to: to.map(|to| to.parse()).transpose()?,
+            },
+            ("review_requested", None, Some(reviewer)) => Change::ReviewRequested(reviewer),
+            ("review_request_removed", Some(reviewer), None) => {
+                Change::ReviewRequestRemoved(reviewer)
+            }
+            ("released", None, Some(tag)) => Change::Released(tag),
+            ("prereleased", None, Some(tag)) => Change::Prereleased(tag),
+            ("answered", None, None) => Change::Answered,
+            ("ci", from, to) => Change::Ci {
+                from: from.map(|from| from.parse()).transpose()?,
+                to: to.map(|to| to.parse()).transpose()?,
+            },
+            ("reviewed", Some(state), Some(by)) => Change::Reviewed {
+                by,
+                state: state.parse()?,
+            },
+            (kind, _, _) => bail!("unknown or incomplete change: {}", kind),
+        };
+
+        Ok(change)
+    }
+}
+
+/// A change to a tracked item, along with when the updater saw it.
+#[derive(Debug, Clone)]
+pub struct ItemEvent {
+    pub foreign_id: String,
+    pub number: i32,
+    pub change: Change,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Something about a repo as a whole that the updater saw change.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RepoChange {
+    /// The default branch went from passing to failing, or back.
+    BranchHealth { from: CiStatus, to: CiStatus },
+}
+
+impl RepoChange {
+    /// The kind of change along with the values before and after, the way they are stored.
+    pub fn to_parts(&self) -> (&'static str, Option<String>, Option<String>) {
+        match self {
+            RepoChange::BranchHealth { from, to } => (
+                "branch_health",
+                Some(from.to_string()),
+                Some(to.to_string()),
+            ),
         }
-        raw.split(',').map(|l| Label(l.to_owned())).collect()
     }
 
-    pub fn map(raw: &[String]) -> Vec<Label> {
-        raw.iter().map(|r| Label::new(r.to_owned())).collect()
+    pub fn from_parts(kind: &str, from: Option<String>, to: Option<String>) -> Result<Self> {
+        let change = match (kind, from, to) {
+            ("branch_health", Some(from), Some(to)) => RepoChange::BranchHealth {
+                from: from.parse()?,
+                to: to.parse()?,
+            },
+            (kind, _, _) => bail!("unknown or incomplete change to a repo: {}", kind),
+        };
+
+        Ok(change)
+    }
+}
+
+/// A change to a repo, along with when the updater saw it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RepoEvent {
+    pub change: RepoChange,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// The open items of a repo as they currently are on GitHub.
+#[derive(Debug, Clone)]
+pub struct LiveItems {
+    pub items: Vec<NewTrackedItem>,
+    /// Set when there were more items than we were willing to fetch.
+    pub truncated: bool,
+}
+
+/// Tracked items as they currently are on GitHub.
+#[derive(Debug, Clone, Default)]
+pub struct RefreshedItems {
+    pub items: Vec<NewTrackedItem>,
+    /// Node IDs that could not be fetched, unlike the ones left out because they are gone.
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Label {
+    pub name: String,
+    /// The hex code GitHub shows the label in, without a leading `#`.
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+impl Label {
+    pub fn new(name: String) -> Self {
+        Label {
+            name,
+            color: None,
+            description: None,
+        }
     }
 
     pub fn expose(labels: &[Label]) -> Vec<String> {
-        labels.iter().map(|l| l.0.clone()).collect()
+        labels.iter().map(|l| l.name.clone()).collect()
     }
 }
 
@@ -145,10 +805,13 @@ impl<T: Into<String>> From<T> for Label {
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct Author {
+    /// The login of the author on GitHub.
     pub name: String,
-    link: Option<String>,
+    pub link: Option<String>,
+    pub avatar: Option<String>,
+    pub kind: AuthorKind,
 }
 
 impl Author {
@@ -156,6 +819,8 @@ impl Author {
         Author {
             name: name.into(),
             link: None,
+            avatar: None,
+            kind: AuthorKind::User,
         }
     }
 
@@ -163,6 +828,45 @@ impl Author {
         self.link = Some(url.into());
         self
     }
+
+    pub fn with_avatar<S: Into<String>>(mut self, url: S) -> Self {
+        self.avatar = Some(url.into());
+        self
+    }
+
+    pub fn with_kind(mut self, kind: AuthorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum AuthorKind {
+    User,
+    Bot,
+    Organization,
+}
+
+impl Display for AuthorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let val = match self {
+            AuthorKind::User => "user",
+            AuthorKind::Bot => "bot",
+            AuthorKind::Organization => "organization",
+        };
+        write!(f, "{}", val)
+    }
+}
+
+impl From<&str> for AuthorKind {
+    /// Authors stored before their kind was kept, mannequins and the like count as users.
+    fn from(s: &str) -> Self {
+        match s {
+            "bot" => AuthorKind::Bot,
+            "organization" => AuthorKind::Organization,
+            _ => AuthorKind::User,
+        }
+    }
 }
 
 impl<T: Into<String>> From<T> for Author {
@@ -181,130 +885,718 @@ pub fn get_all_repos(db: Arc<dyn Db>) -> anyhow::Result<Vec<api::Repo>> {
     Ok(result)
 }
 
-pub fn add_new_repo(
+/// The history of a tracked item, or `None` if the repo tracks no such item.
+pub fn item_history(
+    db: Arc<dyn Db>,
+    repo: StoredRepo,
+    nr: i32,
+) -> Result<Option<Vec<api::ItemEvent>>> {
+    // Issues, PRs and discussions share their numbers on GitHub
+    let foreign_id = match db.item_foreign_id(&repo, nr)? {
+        Some(foreign_id) => foreign_id,
+        None => return Ok(None),
+    };
+
+    let events = db.history(&repo, &foreign_id)?;
+
+    Ok(Some(events.into_iter().map(api::ItemEvent::from).collect()))
+}
+
+/// Everything recorded about a repo as a whole, oldest first.
+pub fn repo_history(db: Arc<dyn Db>, repo: StoredRepo) -> Result<Vec<api::RepoEvent>> {
+    let events = db.repo_history(&repo)?;
+
+    Ok(events.into_iter().map(api::RepoEvent::from).collect())
+}
+
+pub fn current_rate_limit(client: Arc<dyn ClientForRepositories>) -> Option<api::RateLimit> {
+    client.rate_limit().map(api::RateLimit::from)
+}
+
+pub async fn add_new_repo(
     db: Arc<dyn Db>,
     client: Arc<dyn ClientForRepositories>,
     maybe_name: String,
 ) -> Result<StoredRepo> {
     let name = RepoName::from(maybe_name)?;
 
-    if client.repo_exists(&name)? {
-        let repo = db.insert_new_repo(&name.to_string())?;
-        Result::Ok(repo)
-    } else {
-        bail!("Repo {} not found on GitHub.", name.to_string())
+    let remote = match client.repo_exists(&name).await? {
+        Some(remote) => remote,
+        None => return Err(RepoNotFound(name.to_string()).into()),
+    };
+
+    // Only GitHub knows how the name is spelled
+    if db.find_repo(&remote.name.to_string()).is_some() {
+        return Err(RepoAlreadyExists(remote.name.to_string()).into());
     }
+
+    db.insert_remote_repo(&remote)
 }
 
 pub async fn retrieve_live_items(
     client: Arc<dyn ClientForRepositories>,
     repo: StoredRepo,
-) -> Result<Vec<api::Item>> {
+) -> Result<api::LiveItems> {
     let name = repo.name();
-    Result::Ok(
-        client
-            .entire_repo(&name)?
-            .into_iter()
-            .map(crate::domain::api::Item::from)
-            .collect::<Vec<_>>(),
-    )
+    Result::Ok(api::LiveItems::from(client.entire_repo(&name).await?))
+}
+
+/// How many issues and PRs are looked up on GitHub at the same time.
+const CONCURRENT_LOOKUPS: usize = 8;
+
+/// What became of a single item someone asked to track.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TrackingOutcome {
+    Tracked,
+    AlreadyTracked,
+    NotFound,
+    /// The number exists, but is a PR when an issue was asked for or vice versa.
+    WrongKind,
+    Failed(String),
 }
 
 pub async fn add_items_to_track(
     db: Arc<dyn Db>,
     client: Arc<dyn ClientForRepositories>,
+    untracked: &updater::Untracked,
     repo: StoredRepo,
     items: Vec<api::ItemToTrack>,
-) -> Result<()> {
-    let mut tasks = FuturesUnordered::new();
-    for item in items {
-        let name = repo.name();
-        let c = client.clone();
-        tasks.push(task::spawn(async move {
-            match item.kind {
-                ItemKind::Issue => c.issue(&name, item.nr),
-                ItemKind::PR => c.pull_request(&name, item.nr),
+) -> Result<api::TrackingReport> {
+    let tracked = db
+        .all()?
+        .into_iter()
+        .find(|r| r.id == repo.id)
+        .possibly("repo is not tracked")?
+        .items();
+
+    let mut seen = tracked
+        .iter()
+        .map(|item| (item.kind(), item.number))
+        .collect::<HashSet<_>>();
+
+    let mut outcomes = Vec::new();
+    let mut to_look_up = Vec::new();
+    for (idx, item) in items.into_iter().enumerate() {
+        if seen.insert((item.kind.clone(), item.nr)) {
+            to_look_up.push((idx, item));
+        } else {
+            outcomes.push((idx, item, TrackingOutcome::AlreadyTracked));
+        }
+    }
+
+    let name = repo.name();
+    let looked_up = stream::iter(to_look_up)
+        .map(|(idx, item)| {
+            let client = client.clone();
+            let name = name.clone();
+            async move {
+                let found = look_up(client.as_ref(), &name, &item).await;
+                (idx, item, found)
             }
-        }));
+        })
+        .buffer_unordered(CONCURRENT_LOOKUPS)
+        .collect::<Vec<_>>()
+        .await;
 
-        // TODO:  I am surprised I have to do this instead of tasks.collect().await
-        let mut res = Vec::new();
-        while let Some(i) = tasks.next().await {
-            let inner = i?;
-            res.push(inner);
+    let mut new_items = Vec::new();
+    for (idx, item, found) in looked_up {
+        match found {
+            Ok(new_item) => {
+                new_items.push(new_item);
+                outcomes.push((idx, item, TrackingOutcome::Tracked));
+            }
+            Err(outcome) => outcomes.push((idx, item, outcome)),
         }
+    }
 
-        db.insert_tracked_items(&repo, res)?;
+    for item in new_items.iter() {
+        untracked.remove(&item.foreign_id);
     }
+    db.insert_tracked_items(&repo, new_items)?;
 
-    Ok(())
+    outcomes.sort_by_key(|(idx, _, _)| *idx);
+    Ok(api::TrackingReport::from(
+        outcomes
+            .into_iter()
+            .map(|(_, item, outcome)| (item, outcome))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Stops tracking an issue, PR or discussion, returning whether it was tracked in the first place.
+pub fn untrack_item(
+    db: Arc<dyn Db>,
+    untracked: &updater::Untracked,
+    repo: StoredRepo,
+    kind: ItemKind,
+    nr: i32,
+) -> Result<bool> {
+    // The updater would bring it back with the next pass anyway
+    if kind == ItemKind::Release {
+        return Err(ReleaseNotUntrackable(nr).into());
+    }
+
+    let item = db
+        .all()?
+        .into_iter()
+        .find(|r| r.id == repo.id)
+        .possibly("repo is not tracked")?
+        .items()
+        .into_iter()
+        .find(|item| item.kind() == kind && item.number == nr);
+
+    match item {
+        Some(item) => {
+            untracked.insert(&item.foreign_id);
+            db.remove_tracked_item(&repo, item)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+async fn look_up(
+    client: &dyn ClientForRepositories,
+    name: &RepoName,
+    item: &api::ItemToTrack,
+) -> std::result::Result<NewTrackedItem, TrackingOutcome> {
+    if item.kind == ItemKind::Release {
+        return Err(TrackingOutcome::Failed(
+            "releases of tracked repos are picked up on their own".to_string(),
+        ));
+    }
+
+    let error = match fetch(client, name, &item.kind, item.nr).await {
+        Ok(mut found) => {
+            // The item is worth tracking even without its timeline
+            if found.kind().has_timeline() {
+                match client.timeline(name, item.nr).await {
+                    Ok(timeline) => found.timeline = timeline,
+                    Err(e) => log::warn!("could not fetch the timeline of {}: {:#}", item.nr, e),
+                }
+            }
+            return Ok(found);
+        }
+        Err(error) => error,
+    };
+
+    match error.downcast_ref::<ClientError>() {
+        Some(ClientError::NotFound(_)) => {
+            // Issues, PRs and discussions share their numbers, so it might just be another kind
+            let others = [ItemKind::Issue, ItemKind::PR, ItemKind::Discussion];
+            for other in others.iter().filter(|other| **other != item.kind) {
+                if fetch(client, name, other, item.nr).await.is_ok() {
+                    return Err(TrackingOutcome::WrongKind);
+                }
+            }
+
+            Err(TrackingOutcome::NotFound)
+        }
+        _ => Err(TrackingOutcome::Failed(format!("{:#}", error))),
+    }
+}
+
+/// Looks up a single issue, PR or discussion by its number.
+async fn fetch(
+    client: &dyn ClientForRepositories,
+    name: &RepoName,
+    kind: &ItemKind,
+    nr: i32,
+) -> Result<NewTrackedItem> {
+    match kind {
+        ItemKind::Issue => client.issue(name, nr).await,
+        ItemKind::PR => client.pull_request(name, nr).await,
+        ItemKind::Discussion => client.discussion(name, nr).await,
+        ItemKind::Release => bail!("releases can not be looked up by their number"),
+    }
 }
 
 #[cfg(test)]
 mod test {
     use anyhow::Result;
     use async_std::task;
-    use mockall::mock;
 
-    use crate::db::{Db, FullStoredRepo, StoredRepo};
+    use crate::db::StoredRepo;
 
     use super::*;
 
-    mock!(
-        pub Database { }
-        trait Db {
-            fn find_repo(&self, repo_name: &str) -> Option<StoredRepo>;
-            fn insert_tracked_items(
-                &self,
-                repo: &StoredRepo,
-                items: Vec<NewTrackedItem>,
-            ) -> Result<()>;
-            fn update_tracked_item(&self, repo: &StoredRepo, item: NewTrackedItem) -> Result<()>;
-            fn remove_tracked_item(&self, repo: &StoredRepo, item: NewTrackedItem) -> Result<()>;
-            fn all(&self) -> Result<Vec<FullStoredRepo>>;
-            fn insert_new_repo(&self, repo_name: &str) -> Result<StoredRepo>;
-            fn delete(&self, repo: StoredRepo) -> Result<()>;
-        }
-    );
-
-    mock!(
-        pub Github{ }
-
-        trait ClientForRepositories{
-            fn repo_exists(&self, repo: &RepoName) -> Result<bool>;
-            fn entire_repo(&self, repo: &RepoName) -> Result<Vec<NewTrackedItem>>;
-            fn issue(&self, repo: &RepoName, nr: i32) -> Result<NewTrackedItem>;
-            fn pull_request(&self, repo: &RepoName, nr: i32) -> Result<NewTrackedItem>;
-        }
-    );
+    /// Stands in for GitHub, knowing only about the items it was given. Every repo asked
+    /// about exists, under its name in lower case, unless told otherwise.
+    struct StubGithub {
+        items: Vec<NewTrackedItem>,
+        /// Handed out as the timeline of any item.
+        timeline: Vec<TimelineEvent>,
+        /// Whether any repo asked about exists.
+        knows_repos: bool,
+    }
+
+    impl StubGithub {
+        fn new() -> Self {
+            StubGithub {
+                items: Vec::new(),
+                timeline: Vec::new(),
+                knows_repos: true,
+            }
+        }
+
+        fn find(&self, kind: ItemKind, nr: i32) -> Result<NewTrackedItem> {
+            self.items
+                .iter()
+                .find(|item| item.kind() == kind && item.number == nr)
+                .cloned()
+                .ok_or_else(|| ClientError::NotFound("no such item".into()).into())
+        }
+    }
+
+    #[async_trait]
+    impl ClientForRepositories for StubGithub {
+        fn rate_limit(&self) -> Option<RateLimit> {
+            None
+        }
+
+        async fn repo_exists(&self, repo: &RepoName) -> Result<Option<RemoteRepo>> {
+            if !self.knows_repos {
+                return Ok(None);
+            }
+
+            Ok(Some(RemoteRepo {
+                foreign_id: format!("repo-{}", repo),
+                name: RepoName {
+                    owner: repo.owner.to_lowercase(),
+                    name: repo.name.to_lowercase(),
+                },
+            }))
+        }
+
+        async fn entire_repo(&self, _repo: &RepoName) -> Result<LiveItems> {
+            Ok(LiveItems {
+                items: self.items.clone(),
+                truncated: false,
+            })
+        }
+
+        async fn issue(&self, _repo: &RepoName, nr: i32) -> Result<NewTrackedItem> {
+            self.find(ItemKind::Issue, nr)
+        }
+
+        async fn pull_request(&self, _repo: &RepoName, nr: i32) -> Result<NewTrackedItem> {
+            self.find(ItemKind::PR, nr)
+        }
+
+        async fn timeline(&self, _repo: &RepoName, _nr: i32) -> Result<Vec<TimelineEvent>> {
+            Ok(self.timeline.clone())
+        }
+
+        async fn refresh_items(&self, foreign_ids: &[String]) -> Result<RefreshedItems> {
+            Ok(RefreshedItems {
+                items: self
+                    .items
+                    .iter()
+                    .filter(|item| foreign_ids.contains(&item.foreign_id))
+                    .cloned()
+                    .collect(),
+                failed: Vec::new(),
+            })
+        }
+
+        async fn refresh_repos(&self, _foreign_ids: &[String]) -> Result<Vec<RemoteRepo>> {
+            Ok(Vec::new())
+        }
+
+        async fn discussion(&self, _repo: &RepoName, nr: i32) -> Result<NewTrackedItem> {
+            self.find(ItemKind::Discussion, nr)
+        }
+
+        async fn releases(
+            &self,
+            _foreign_ids: &[String],
+        ) -> Result<HashMap<String, Vec<NewTrackedItem>>> {
+            Ok(HashMap::new())
+        }
+
+        async fn last_commits(&self, _foreign_ids: &[String]) -> Result<HashMap<String, Commit>> {
+            Ok(HashMap::new())
+        }
+    }
+
+    fn item(kind: ItemKind, nr: i32) -> NewTrackedItem {
+        let details = match kind {
+            ItemKind::PR => ItemDetails::PR {
+                reviews: Reviews::default(),
+                checks: Checks::default(),
+            },
+            ItemKind::Issue => ItemDetails::Issue,
+            ItemKind::Discussion => ItemDetails::Discussion(Discussion {
+                category: "Q&A".to_string(),
+                answered: false,
+                comments: 0,
+            }),
+            ItemKind::Release => unreachable!("releases are never looked up"),
+        };
+        NewTrackedItem {
+            title: format!("item {}", nr),
+            state: State::Open,
+            link: format!("https://github.com/foo/bar/{}", nr),
+            by: Author::new("octocat"),
+            labels: vec![],
+            labels_truncated: false,
+            timeline: vec![],
+            details,
+            foreign_id: format!("node-{}", nr),
+            last_updated: Utc::now(),
+            number: nr,
+            closed_at: None,
+            closed_by: None,
+        }
+    }
+
+    fn track(kind: ItemKind, nr: i32) -> api::ItemToTrack {
+        api::ItemToTrack { kind, nr }
+    }
+
+    fn outcomes(report: &api::TrackingReport) -> Vec<(i32, &str)> {
+        report
+            .items
+            .iter()
+            .map(|result| (result.nr, result.outcome.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn stores_reviews_as_their_own_kind_of_change() {
+        let reviewed = Change::Reviewed {
+            by: "hubot".to_string(),
+            state: ReviewState::Approved,
+        };
+
+        let (kind, from, to) = reviewed.to_parts();
+        assert_eq!(kind, "reviewed");
+        assert_eq!(from.as_deref(), Some("approved"));
+        assert_eq!(to.as_deref(), Some("hubot"));
+
+        assert_eq!(Change::from_parts(kind, from, to).unwrap(), reviewed);
+        assert!(Change::from_parts("approved", None, Some("hubot".to_string())).is_err());
+    }
+
+    #[test]
+    fn understands_the_usual_ways_of_naming_a_repo() {
+        for input in &[
+            "owner/name",
+            " owner/name/ ",
+            "owner/name.git",
+            "https://github.com/owner/name",
+            "https://github.com/owner/name.git",
+            "http://github.com/owner/name/",
+            "https://github.com/owner/name?tab=readme#top",
+            "git@github.com:owner/name.git",
+            "ssh://git@github.com/owner/name.git",
+            "https://github.example.com/owner/name",
+        ] {
+            let name = RepoName::from(*input).unwrap();
+            assert_eq!(name.to_string(), "owner/name", "parsing {}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_names_that_are_not_exactly_a_repo() {
+        for input in &[
+            "",
+            "owner",
+            "owner/",
+            "/name",
+            "owner/name/extra",
+            "https://github.com/owner",
+            "https://github.com/owner/name/pull/3",
+            "owner/na me",
+            "../name",
+        ] {
+            let error = RepoName::from(*input).unwrap_err();
+            assert!(error.is::<InvalidRepoName>(), "parsing {}", input);
+        }
+    }
+
+    #[test]
+    fn stores_the_name_as_github_spells_it() {
+        let db = crate::db::in_memory().unwrap();
+
+        let repo = task::block_on(add_new_repo(
+            db.clone(),
+            Arc::new(StubGithub::new()),
+            "https://github.com/Foo/Bar.git".to_string(),
+        ))
+        .unwrap();
+
+        assert_eq!(repo.title, "foo/bar");
+        assert_eq!(db.find_repo("foo/bar").unwrap().foreign_id, repo.foreign_id);
+        assert!(repo.foreign_id.is_some());
+    }
+
+    #[test]
+    fn does_not_add_a_repo_twice() {
+        let db = crate::db::in_memory().unwrap();
+        db.insert_new_repo("foo/bar").unwrap();
+
+        // GitHub knows it as foo/bar
+        let result = task::block_on(add_new_repo(
+            db.clone(),
+            Arc::new(StubGithub::new()),
+            "Foo/Bar".to_string(),
+        ));
+
+        assert!(result.unwrap_err().is::<RepoAlreadyExists>());
+        assert_eq!(db.all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn does_not_add_a_repo_github_does_not_know() {
+        let db = crate::db::in_memory().unwrap();
+        let mut github = StubGithub::new();
+        github.knows_repos = false;
+
+        let result = task::block_on(add_new_repo(
+            db.clone(),
+            Arc::new(github),
+            "foo/bar".to_string(),
+        ));
+
+        assert!(result.unwrap_err().is::<RepoNotFound>());
+        assert!(db.all().unwrap().is_empty());
+    }
 
     #[test]
-    #[ignore]
     fn does_not_add_items_to_a_non_existing_repo() {
-        let db = MockDatabase::new();
-        let github = MockGithub::new();
+        let db = crate::db::in_memory().unwrap();
+        let github = StubGithub::new();
 
         let repo = StoredRepo::new(32, "foo/bar");
 
         let result = task::block_on(async move {
-            add_items_to_track(Arc::new(db), Arc::new(github), repo, Vec::new()).await
+            add_items_to_track(
+                db,
+                Arc::new(github),
+                &updater::Untracked::default(),
+                repo,
+                vec![track(ItemKind::Issue, 1)],
+            )
+            .await
         });
 
         assert!(result.is_err(), "should have failed to to add items");
     }
 
     #[test]
-    #[ignore]
     fn queries_github_for_details_on_items_and_stores_them() {
-        let db = MockDatabase::new();
-        let github = MockGithub::new();
+        let db = crate::db::in_memory().unwrap();
+        let repo = db.insert_new_repo("foo/bar").unwrap();
+        let mut github = StubGithub::new();
+        github.items = vec![item(ItemKind::Issue, 1), item(ItemKind::PR, 2)];
 
-        let repo = StoredRepo::new(32, "foo/bar");
+        let report = task::block_on(add_items_to_track(
+            db.clone(),
+            Arc::new(github),
+            &updater::Untracked::default(),
+            repo,
+            vec![track(ItemKind::Issue, 1), track(ItemKind::PR, 2)],
+        ))
+        .unwrap();
 
-        let result = task::block_on(async move {
-            add_items_to_track(Arc::new(db), Arc::new(github), repo, Vec::new()).await
-        });
+        assert_eq!(outcomes(&report), vec![(1, "tracked"), (2, "tracked")]);
 
-        assert!(result.is_err(), "should have failed to to add items");
+        let stored = db.all().unwrap()[0].items();
+        assert_eq!(stored.len(), 2);
+    }
+
+    #[test]
+    fn keeps_the_timeline_of_newly_tracked_items() {
+        let db = crate::db::in_memory().unwrap();
+        let repo = db.insert_new_repo("foo/bar").unwrap();
+        let mut github = StubGithub::new();
+        github.items = vec![item(ItemKind::Issue, 1)];
+        github.timeline = vec![TimelineEvent {
+            kind: TimelineEventKind::Commented,
+            by: "hubot".into(),
+            at: Utc::now(),
+            detail: Some("Can reproduce".into()),
+        }];
+        let timeline = github.timeline.clone();
+
+        task::block_on(add_items_to_track(
+            db.clone(),
+            Arc::new(github),
+            &updater::Untracked::default(),
+            repo,
+            vec![track(ItemKind::Issue, 1)],
+        ))
+        .unwrap();
+
+        assert_eq!(db.all().unwrap()[0].items()[0].timeline, timeline);
+    }
+
+    #[test]
+    fn reports_what_happened_to_each_item() {
+        let db = crate::db::in_memory().unwrap();
+        let repo = db.insert_new_repo("foo/bar").unwrap();
+        db.insert_tracked_items(&repo, vec![item(ItemKind::Issue, 1)])
+            .unwrap();
+        let mut github = StubGithub::new();
+        github.items = vec![
+            item(ItemKind::Issue, 1),
+            item(ItemKind::PR, 2),
+            item(ItemKind::Issue, 3),
+        ];
+
+        let report = task::block_on(add_items_to_track(
+            db.clone(),
+            Arc::new(github),
+            &updater::Untracked::default(),
+            repo,
+            vec![
+                track(ItemKind::Issue, 1),
+                track(ItemKind::Issue, 2),
+                track(ItemKind::Issue, 3),
+                track(ItemKind::Issue, 3),
+                track(ItemKind::PR, 4),
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(
+            outcomes(&report),
+            vec![
+                (1, "already_tracked"),
+                (2, "wrong_kind"),
+                (3, "tracked"),
+                (3, "already_tracked"),
+                (4, "not_found"),
+            ]
+        );
+
+        let stored = db.all().unwrap()[0].items();
+        assert_eq!(stored.len(), 2);
+    }
+
+    #[test]
+    fn tracks_discussions_without_a_timeline() {
+        let db = crate::db::in_memory().unwrap();
+        let repo = db.insert_new_repo("foo/bar").unwrap();
+        let mut github = StubGithub::new();
+        github.items = vec![item(ItemKind::Discussion, 5), item(ItemKind::Issue, 6)];
+        github.timeline = vec![TimelineEvent {
+            kind: TimelineEventKind::Commented,
+            by: "hubot".into(),
+            at: Utc::now(),
+            detail: None,
+        }];
+
+        let report = task::block_on(add_items_to_track(
+            db.clone(),
+            Arc::new(github),
+            &updater::Untracked::default(),
+            repo,
+            vec![
+                track(ItemKind::Discussion, 5),
+                track(ItemKind::Discussion, 6),
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(outcomes(&report), vec![(5, "tracked"), (6, "wrong_kind")]);
+
+        let discussions = &db.all().unwrap()[0].discussions;
+        assert_eq!(discussions.len(), 1);
+        assert!(discussions[0].timeline.is_empty());
+    }
+
+    #[test]
+    fn untracks_a_single_item() {
+        let db = crate::db::in_memory().unwrap();
+        let repo = db.insert_new_repo("foo/bar").unwrap();
+        db.insert_tracked_items(&repo, vec![item(ItemKind::Issue, 1), item(ItemKind::PR, 2)])
+            .unwrap();
+        let untracked = updater::Untracked::default();
+
+        let removed = untrack_item(db.clone(), &untracked, repo.clone(), ItemKind::PR, 2).unwrap();
+        assert!(removed);
+
+        let left = db.all().unwrap()[0].items();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].number, 1);
+
+        let removed_again = untrack_item(db.clone(), &untracked, repo, ItemKind::PR, 2).unwrap();
+        assert!(!removed_again);
+    }
+
+    #[test]
+    fn does_not_untrack_an_item_of_another_kind() {
+        let db = crate::db::in_memory().unwrap();
+        let repo = db.insert_new_repo("foo/bar").unwrap();
+        db.insert_tracked_items(&repo, vec![item(ItemKind::Issue, 1)])
+            .unwrap();
+
+        let removed = untrack_item(
+            db.clone(),
+            &updater::Untracked::default(),
+            repo,
+            ItemKind::PR,
+            1,
+        )
+        .unwrap();
+
+        assert!(!removed);
+        assert_eq!(db.all().unwrap()[0].items().len(), 1);
+    }
+
+    #[test]
+    fn keeps_the_history_of_an_item_after_it_was_untracked() {
+        let db = crate::db::in_memory().unwrap();
+        let repo = db.insert_new_repo("foo/bar").unwrap();
+        db.insert_tracked_items(&repo, vec![item(ItemKind::Issue, 1)])
+            .unwrap();
+        db.record_events(
+            &repo,
+            vec![ItemEvent {
+                foreign_id: "node-1".to_string(),
+                number: 1,
+                change: Change::LabelAdded("bug".to_string()),
+                recorded_at: Utc::now(),
+            }],
+        )
+        .unwrap();
+
+        untrack_item(
+            db.clone(),
+            &updater::Untracked::default(),
+            repo.clone(),
+            ItemKind::Issue,
+            1,
+        )
+        .unwrap();
+
+        let history = item_history(db.clone(), repo.clone(), 1).unwrap().unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(item_history(db, repo, 2).unwrap().is_none());
+    }
+
+    #[test]
+    fn does_not_untrack_releases() {
+        let db = crate::db::in_memory().unwrap();
+        let repo = db.insert_new_repo("foo/bar").unwrap();
+        let release = NewTrackedItem {
+            details: ItemDetails::Release(Release {
+                tag: "v1.0.0".to_string(),
+                prerelease: false,
+                published_at: Utc::now(),
+            }),
+            foreign_id: "release-7".to_string(),
+            number: 7,
+            ..item(ItemKind::Issue, 7)
+        };
+        db.insert_tracked_items(&repo, vec![release]).unwrap();
+        let untracked = updater::Untracked::default();
+
+        let error = untrack_item(db.clone(), &untracked, repo, ItemKind::Release, 7).unwrap_err();
+
+        assert!(error.is::<ReleaseNotUntrackable>());
+        assert_eq!(db.all().unwrap()[0].releases.len(), 1);
     }
 }
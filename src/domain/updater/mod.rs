@@ -1,63 +1,1302 @@
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_std::prelude::*;
 use async_std::sync::Receiver;
 use async_std::task;
+use chrono::{DateTime, Utc};
 
-use crate::db::{Db, StoredRepo};
-use crate::domain::{ClientForRepositories, ItemKind, NewTrackedItem, State};
+use crate::db::{Db, FullStoredRepo, StoredRepo};
+use crate::domain::{
+    Author, BranchHealth, Change, CiStatus, ClientForRepositories, Commit, ItemDetails, ItemEvent,
+    ItemKind, Label, NewTrackedItem, RateLimit, RefreshedItems, RemoteRepo, RepoChange, RepoEvent,
+    ReviewState,
+};
+
+/// Rate limit points the updater leaves untouched so that browsing repos keeps working.
+pub const DEFAULT_RATE_LIMIT_RESERVE: i64 = 100;
+
+/// How many hours closed and merged items stay around so people notice they were resolved.
+pub const DEFAULT_KEEP_RESOLVED_HOURS: u32 = 24;
+
+/// Items that stopped being tracked, so that passes which were queued before
+/// don't bring them back or spend rate limit on them.
+#[derive(Clone, Default)]
+pub struct Untracked {
+    foreign_ids: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Untracked {
+    pub fn insert(&self, foreign_id: &str) {
+        self.foreign_ids
+            .lock()
+            .unwrap()
+            .insert(foreign_id.to_string());
+    }
+
+    /// Tracking an item again lets the updater pick it back up.
+    pub fn remove(&self, foreign_id: &str) {
+        self.foreign_ids.lock().unwrap().remove(foreign_id);
+    }
+
+    fn contains(&self, foreign_id: &str) -> bool {
+        self.foreign_ids.lock().unwrap().contains(foreign_id)
+    }
+
+    /// Passes are read from the database after the item was removed, so once one of them no
+    /// longer has it, it does not need to be kept out anymore.
+    fn keep_only(&self, foreign_ids: &[String]) {
+        let in_pass = foreign_ids.iter().collect::<HashSet<_>>();
+        self.foreign_ids
+            .lock()
+            .unwrap()
+            .retain(|foreign_id| in_pass.contains(foreign_id));
+    }
+}
 
 pub struct Config {
-    pub channel: Receiver<(StoredRepo, NewTrackedItem)>,
+    /// Every message is one full pass over all tracked repos and their items.
+    pub channel: Receiver<Vec<FullStoredRepo>>,
     pub client: Arc<dyn ClientForRepositories>,
     pub db: Arc<dyn Db>,
+    pub rate_limit_reserve: i64,
+    /// Closed and merged items are removed once they were resolved for longer than this.
+    pub keep_resolved: chrono::Duration,
+    pub untracked: Untracked,
 }
 
 pub fn start(config: Config) {
     task::spawn(async move {
         let db = config.db;
         let client = config.client;
-        let mut inbound = config.channel.throttle(Duration::from_secs(1));
-        while let Some((repo, item)) = inbound.next().await {
-            let updated = match item.kind {
-                ItemKind::PR => client.pull_request(&repo.name(), item.number),
-                ItemKind::Issue => client.issue(&repo.name(), item.number),
-            };
+        let reserve = config.rate_limit_reserve;
+        let untracked = config.untracked;
+        let keep_resolved = config.keep_resolved;
+        let mut inbound = config.channel;
+        while let Some(repos) = inbound.next().await {
+            if let Some(pause) = pause_for(client.rate_limit(), reserve, Utc::now()) {
+                log::info!("rate limit is running low, pausing updates for {:?}", pause);
+                task::sleep(pause).await;
+            }
+
+            let repos = follow_renames(db.as_ref(), client.as_ref(), repos).await;
+            refresh_last_commits(db.as_ref(), client.as_ref(), &repos).await;
+            refresh_releases(db.as_ref(), client.as_ref(), &repos).await;
 
-            if updated.is_err() {
-                continue;
+            let mut tracked = Vec::new();
+            for repo in repos {
+                // Releases were just refreshed along with their repo
+                for item in repo
+                    .items()
+                    .into_iter()
+                    .filter(|item| item.kind() != ItemKind::Release)
+                {
+                    tracked.push((repo.stored(), item));
+                }
             }
 
-            let updated = updated.unwrap();
+            let ids = tracked
+                .iter()
+                .map(|(_, item)| item.foreign_id.clone())
+                .collect::<Vec<_>>();
+            untracked.keep_only(&ids);
 
-            let result = match update(item, updated) {
-                Outcome::Update(u) => db.update_tracked_item(&repo, u),
-                Outcome::Remove(u) => db.remove_tracked_item(&repo, u),
-                Outcome::Ignore => Result::Ok(()),
+            let refreshed = match client.refresh_items(&ids).await {
+                Ok(refreshed) => refreshed,
+                Err(e) => {
+                    log::warn!("could not refresh {} tracked items: {:#}", ids.len(), e);
+                    continue;
+                }
             };
 
-            result.expect("it should have worked!")
+            // Untracking might have happened while we waited on GitHub
+            let tracked = tracked
+                .into_iter()
+                .filter(|(_, item)| !untracked.contains(&item.foreign_id))
+                .collect();
+
+            let now = Utc::now();
+            for (repo, outcome) in compare(tracked, refreshed, keep_resolved, now) {
+                let result = match outcome {
+                    Outcome::Update(u, changes) => db
+                        .record_events(&repo, events(&u, changes, now))
+                        .and_then(|_| db.update_tracked_item(&repo, u)),
+                    Outcome::Remove(r, changes) => db
+                        .record_events(&repo, events(&r, changes, now))
+                        .and_then(|_| db.remove_tracked_item(&repo, r)),
+                    Outcome::Ignore => Result::Ok(()),
+                };
+
+                if let Err(e) = result {
+                    log::warn!("could not store the changes to {}: {:#}", repo.title, e);
+                }
+            }
         }
     });
 }
 
+/// The changes to an item as they go into its history.
+fn events(item: &NewTrackedItem, changes: Vec<Change>, now: DateTime<Utc>) -> Vec<ItemEvent> {
+    changes
+        .into_iter()
+        .map(|change| ItemEvent {
+            foreign_id: item.foreign_id.clone(),
+            number: item.number,
+            change,
+            recorded_at: now,
+        })
+        .collect()
+}
+
+/// Brings the stored titles in line with what the repos are called on GitHub now,
+/// so that repos which were renamed or transferred keep being updated.
+async fn follow_renames(
+    db: &dyn Db,
+    client: &dyn ClientForRepositories,
+    mut repos: Vec<FullStoredRepo>,
+) -> Vec<FullStoredRepo> {
+    let mut remote = Vec::new();
+
+    // Repos added before we kept their node ID are looked up by name, once
+    for repo in repos.iter_mut().filter(|repo| repo.foreign_id.is_none()) {
+        match client.repo_exists(&repo.stored().name()).await {
+            Ok(Some(found)) => match db.set_repo_foreign_id(&repo.stored(), &found.foreign_id) {
+                Ok(()) => {
+                    repo.foreign_id = Some(found.foreign_id.clone());
+                    remote.push(found);
+                }
+                Err(e) => log::warn!("could not store the node ID of {}: {:#}", repo.title, e),
+            },
+            Ok(None) => log::warn!("{} can not be found on GitHub", repo.title),
+            Err(e) => log::warn!("could not look up {}: {:#}", repo.title, e),
+        }
+    }
+
+    let ids = repos
+        .iter()
+        .filter_map(|repo| repo.foreign_id.clone())
+        .collect::<Vec<_>>();
+
+    if !ids.is_empty() {
+        match client.refresh_repos(&ids).await {
+            Ok(found) => remote.extend(found),
+            Err(e) => log::warn!("could not refresh {} repos: {:#}", ids.len(), e),
+        }
+    }
+
+    for (repo, new_title) in renames(&repos, remote) {
+        match db.rename_repo(&repo, &new_title) {
+            Ok(renamed) => {
+                log::info!("{} is now known as {}", repo.title, renamed.title);
+                if let Some(stored) = repos.iter_mut().find(|r| r.id == renamed.id) {
+                    stored.title = renamed.title;
+                    stored.renamed_from = renamed.renamed_from;
+                    stored.renamed_at = renamed.renamed_at;
+                }
+            }
+            Err(e) => log::warn!("could not rename {} to {}: {:#}", repo.title, new_title, e),
+        }
+    }
+
+    repos
+}
+
+/// Keeps the latest commit on the default branch of every repo up to date,
+/// along with whether that branch is green.
+async fn refresh_last_commits(
+    db: &dyn Db,
+    client: &dyn ClientForRepositories,
+    repos: &[FullStoredRepo],
+) {
+    let ids = repos
+        .iter()
+        .filter_map(|repo| repo.foreign_id.clone())
+        .collect::<Vec<_>>();
+
+    if ids.is_empty() {
+        return;
+    }
+
+    let mut commits = match client.last_commits(&ids).await {
+        Ok(commits) => commits,
+        Err(e) => {
+            log::warn!(
+                "could not fetch the last commits of {} repos: {:#}",
+                ids.len(),
+                e
+            );
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    for repo in repos {
+        let commit = match repo
+            .foreign_id
+            .as_ref()
+            .and_then(|foreign_id| commits.remove(foreign_id))
+        {
+            Some(commit) => commit,
+            None => continue,
+        };
+
+        if let Some(health) = branch_health(repo.branch_health.as_ref(), &commit, now) {
+            log::info!(
+                "{} of {} is now {}",
+                commit.branch,
+                repo.title,
+                health.status
+            );
+            let events = branch_events(repo.branch_health.as_ref(), &health, now);
+            if let Err(e) = db.record_repo_events(&repo.stored(), events) {
+                log::warn!("could not store the changes to {}: {:#}", repo.title, e);
+            }
+            if let Err(e) = db.set_branch_health(&repo.stored(), health) {
+                log::warn!(
+                    "could not store the branch health of {}: {:#}",
+                    repo.title,
+                    e
+                );
+            }
+        }
+
+        if repo.last_commit.as_ref() != Some(&commit) {
+            if let Err(e) = db.set_last_commit(&repo.stored(), commit) {
+                log::warn!("could not store the last commit of {}: {:#}", repo.title, e);
+            }
+        }
+    }
+}
+
+/// The new health of the default branch, if its head commit turned it red or green.
+fn branch_health(
+    current: Option<&BranchHealth>,
+    commit: &Commit,
+    now: DateTime<Utc>,
+) -> Option<BranchHealth> {
+    let status = match &commit.ci_status {
+        Some(status @ CiStatus::Passing) | Some(status @ CiStatus::Failing) => status.clone(),
+        _ => return None,
+    };
+
+    match current {
+        Some(health) if health.status == status => None,
+        Some(_) => Some(BranchHealth {
+            status,
+            since: Some(now),
+        }),
+        // The branch might have been like this for ages
+        None => Some(BranchHealth {
+            status,
+            since: None,
+        }),
+    }
+}
+
+/// The default branch turning red or green. How it was first seen is not news.
+fn branch_events(
+    current: Option<&BranchHealth>,
+    health: &BranchHealth,
+    now: DateTime<Utc>,
+) -> Vec<RepoEvent> {
+    match current {
+        Some(current) if current.status != health.status => vec![RepoEvent {
+            change: RepoChange::BranchHealth {
+                from: current.status.clone(),
+                to: health.status.clone(),
+            },
+            recorded_at: now,
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Keeps the latest releases of every repo, drops the ones that fell out of that window
+/// and records the releases that came out since the last pass.
+async fn refresh_releases(
+    db: &dyn Db,
+    client: &dyn ClientForRepositories,
+    repos: &[FullStoredRepo],
+) {
+    let ids = repos
+        .iter()
+        .filter_map(|repo| repo.foreign_id.clone())
+        .collect::<Vec<_>>();
+
+    if ids.is_empty() {
+        return;
+    }
+
+    let mut releases = match client.releases(&ids).await {
+        Ok(releases) => releases,
+        Err(e) => {
+            log::warn!(
+                "could not fetch the releases of {} repos: {:#}",
+                ids.len(),
+                e
+            );
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    for repo in repos {
+        // Repos whose releases could not be fetched keep the ones they have
+        let latest = match repo
+            .foreign_id
+            .as_ref()
+            .and_then(|foreign_id| releases.remove(foreign_id))
+        {
+            Some(latest) => latest,
+            None => continue,
+        };
+
+        let stored = repo.stored();
+        let events = release_events(&repo.releases, &latest, now);
+        let result = db
+            .record_events(&stored, events)
+            .and_then(|_| {
+                stale_releases(&repo.releases, &latest)
+                    .into_iter()
+                    .try_for_each(|release| db.remove_tracked_item(&stored, release))
+            })
+            .and_then(|_| db.insert_tracked_items(&stored, latest));
+
+        if let Err(e) = result {
+            log::warn!("could not store the releases of {}: {:#}", repo.title, e);
+        }
+    }
+}
+
+/// Releases that were published since the newest stored one, and prereleases that were
+/// promoted to full releases. The first releases seen for a repo are not news.
+fn release_events(
+    stored: &[NewTrackedItem],
+    latest: &[NewTrackedItem],
+    now: DateTime<Utc>,
+) -> Vec<ItemEvent> {
+    let newest = match stored
+        .iter()
+        .filter_map(|item| item.details.release())
+        .map(|release| release.published_at)
+        .max()
+    {
+        Some(newest) => newest,
+        None => return Vec::new(),
+    };
+
+    latest
+        .iter()
+        .filter_map(|item| {
+            let release = item.details.release()?;
+            let before = stored
+                .iter()
+                .find(|s| s.foreign_id == item.foreign_id)
+                .and_then(|s| s.details.release());
+
+            let change = match before {
+                None if release.published_at > newest => {
+                    if release.prerelease {
+                        Change::Prereleased(release.tag.clone())
+                    } else {
+                        Change::Released(release.tag.clone())
+                    }
+                }
+                Some(before) if before.prerelease && !release.prerelease => {
+                    Change::Released(release.tag.clone())
+                }
+                _ => return None,
+            };
+
+            Some(ItemEvent {
+                foreign_id: item.foreign_id.clone(),
+                number: item.number,
+                change,
+                recorded_at: now,
+            })
+        })
+        .collect()
+}
+
+/// Stored releases that are no longer among the latest ones, or were deleted on GitHub.
+fn stale_releases(stored: &[NewTrackedItem], latest: &[NewTrackedItem]) -> Vec<NewTrackedItem> {
+    stored
+        .iter()
+        .filter(|s| !latest.iter().any(|l| l.foreign_id == s.foreign_id))
+        .cloned()
+        .collect()
+}
+
+/// The repos whose name on GitHub no longer matches their stored title, with their new title.
+fn renames(repos: &[FullStoredRepo], remote: Vec<RemoteRepo>) -> Vec<(StoredRepo, String)> {
+    let by_id = remote
+        .into_iter()
+        .map(|found| (found.foreign_id, found.name.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    repos
+        .iter()
+        .filter_map(|repo| {
+            let current = by_id.get(repo.foreign_id.as_ref()?)?;
+            if *current != repo.title {
+                Some((repo.stored(), current.clone()))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Pairs every tracked item with its refreshed counterpart and decides what to do with it.
+/// Items GitHub no longer knows about, or that could not be fetched, are left alone.
+fn compare(
+    tracked: Vec<(StoredRepo, NewTrackedItem)>,
+    refreshed: RefreshedItems,
+    keep_resolved: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Vec<(StoredRepo, Outcome)> {
+    let failed = refreshed.failed.into_iter().collect::<HashSet<_>>();
+    let mut by_id = refreshed
+        .items
+        .into_iter()
+        .map(|item| (item.foreign_id.clone(), item))
+        .collect::<HashMap<_, _>>();
+
+    tracked
+        .into_iter()
+        .filter_map(|(repo, old)| match by_id.remove(&old.foreign_id) {
+            Some(new) => Some((repo, update(old, new, keep_resolved, now))),
+            // The failed chunk was already logged, the item might still be there
+            None if failed.contains(&old.foreign_id) => None,
+            None => {
+                log::warn!("{} in {} is gone from GitHub", old.number, repo.title);
+                None
+            }
+        })
+        .collect()
+}
+
+/// How long to hold off until the rate limit resets, if we are down to the reserve.
+fn pause_for(rate_limit: Option<RateLimit>, reserve: i64, now: DateTime<Utc>) -> Option<Duration> {
+    let rate_limit = rate_limit?;
+
+    if rate_limit.remaining > reserve {
+        return None;
+    }
+
+    (rate_limit.reset_at - now).to_std().ok()
+}
+
 pub enum Outcome {
-    Update(NewTrackedItem),
+    /// The refreshed item along with everything that changed about it.
+    Update(NewTrackedItem, Vec<Change>),
     Ignore,
-    Remove(NewTrackedItem),
+    /// The refreshed item, resolved long enough ago to go, and what changed about it since.
+    Remove(NewTrackedItem, Vec<Change>),
 }
 
-pub fn update(old: NewTrackedItem, new: NewTrackedItem) -> Outcome {
+pub fn update(
+    old: NewTrackedItem,
+    new: NewTrackedItem,
+    keep_resolved: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Outcome {
     use Outcome::*;
 
-    if new.state == State::Closed {
-        return Remove(old);
+    // An item can be closed and old enough to go in the same pass, its history still gets the close
+    let changes = changes(&old, &new);
+
+    if new.state.is_resolved() {
+        let resolved_at = new.closed_at.unwrap_or(new.last_updated);
+        if now - resolved_at >= keep_resolved {
+            return Remove(new, changes);
+        }
     }
 
-    if new.last_updated != old.last_updated {
-        return Update(new);
+    // Not everything worth storing makes it into the history, e.g. which checks fail
+    if !changes.is_empty() || !same(&old, &new) {
+        return Update(new, changes);
     }
 
     Ignore
 }
+
+/// Whether nothing at all is different about the refreshed item, labels being a set.
+fn same(old: &NewTrackedItem, new: &NewTrackedItem) -> bool {
+    let sorted = |item: &NewTrackedItem| {
+        let mut item = item.clone();
+        item.labels.sort_by(|a, b| a.name.cmp(&b.name));
+        // Only the login of whoever closed the item is stored
+        item.closed_by = item.closed_by.map(|by| Author::new(by.name));
+        item
+    };
+
+    sorted(old) == sorted(new)
+}
+
+/// What is different about the refreshed item, in the order it ends up in the history.
+fn changes(old: &NewTrackedItem, new: &NewTrackedItem) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    if old.title != new.title {
+        changes.push(Change::Title {
+            from: old.title.clone(),
+            to: new.title.clone(),
+        });
+    }
+
+    // Labels are a set, GitHub does not promise to list them in the same order every time.
+    // Truncated lists hold whichever labels came first, so they are no good for comparing.
+    if !old.labels_truncated && !new.labels_truncated {
+        let old_labels = Label::expose(&old.labels)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let new_labels = Label::expose(&new.labels)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let mut added = new_labels.difference(&old_labels).collect::<Vec<_>>();
+        added.sort();
+        let mut removed = old_labels.difference(&new_labels).collect::<Vec<_>>();
+        removed.sort();
+
+        changes.extend(added.into_iter().cloned().map(Change::LabelAdded));
+        changes.extend(removed.into_iter().cloned().map(Change::LabelRemoved));
+    }
+
+    if old.state != new.state {
+        changes.push(Change::State {
+            from: old.state.clone(),
+            to: new.state.clone(),
+        });
+    }
+
+    match (&old.details, &new.details) {
+        (
+            ItemDetails::PR {
+                reviews: old_reviews,
+                checks: old_checks,
+            },
+            ItemDetails::PR { reviews, checks },
+        ) => {
+            if old_reviews.decision != reviews.decision {
+                changes.push(Change::ReviewDecision {
+                    from: old_reviews.decision.clone(),
+                    to: reviews.decision.clone(),
+                });
+            }
+
+            for reviewer in reviews
+                .requested
+                .iter()
+                .filter(|r| !old_reviews.requested.contains(r))
+            {
+                changes.push(Change::ReviewRequested(reviewer.clone()));
+            }
+
+            // Reviews that were started but not submitted yet are nobody else's business
+            let submitted = reviews
+                .latest
+                .iter()
+                .filter(|r| r.state != ReviewState::Pending && !old_reviews.latest.contains(r))
+                .collect::<Vec<_>>();
+
+            // Submitting a review also takes the reviewer off the requested ones
+            for reviewer in old_reviews.requested.iter().filter(|r| {
+                !reviews.requested.contains(r) && !submitted.iter().any(|review| review.by == **r)
+            }) {
+                changes.push(Change::ReviewRequestRemoved(reviewer.clone()));
+            }
+
+            for review in submitted {
+                changes.push(Change::Reviewed {
+                    by: review.by.clone(),
+                    state: review.state.clone(),
+                });
+            }
+
+            if old_checks.status != checks.status {
+                changes.push(Change::Ci {
+                    from: old_checks.status.clone(),
+                    to: checks.status.clone(),
+                });
+            }
+        }
+        (ItemDetails::Discussion(before), ItemDetails::Discussion(after))
+            if !before.answered && after.answered =>
+        {
+            changes.push(Change::Answered);
+        }
+        _ => {}
+    }
+
+    if old.last_updated != new.last_updated {
+        changes.push(Change::Updated {
+            from: old.last_updated,
+            to: new.last_updated,
+        });
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::domain::{
+        Author, Checks, CiStatus, Discussion, Release, RepoName, Review, ReviewDecision, Reviews,
+        State, TimelineEvent, TimelineEventKind,
+    };
+
+    fn item(foreign_id: &str, state: State, last_updated: DateTime<Utc>) -> NewTrackedItem {
+        NewTrackedItem {
+            title: "some PR".to_string(),
+            state,
+            link: "http://foo.bar".to_string(),
+            by: Author::new("Steve Hawking"),
+            labels: vec![],
+            labels_truncated: false,
+            timeline: vec![],
+            details: ItemDetails::PR {
+                reviews: Reviews::default(),
+                checks: Checks::default(),
+            },
+            foreign_id: foreign_id.to_string(),
+            last_updated,
+            number: 1,
+            closed_at: None,
+            closed_by: None,
+        }
+    }
+
+    #[test]
+    fn matches_refreshed_items_by_their_foreign_id() {
+        let repo = StoredRepo::new(1, "foo/bar");
+        let then = Utc::now() - Duration::hours(1);
+        let now = Utc::now();
+
+        let tracked = vec![
+            (repo.clone(), item("a", State::Open, then)),
+            (repo.clone(), item("b", State::Open, then)),
+            (repo.clone(), item("c", State::Open, then)),
+            (repo.clone(), item("gone", State::Open, then)),
+            (repo.clone(), item("not fetched", State::Open, then)),
+        ];
+        let refreshed = RefreshedItems {
+            items: vec![
+                item("c", State::Closed, now),
+                item("a", State::Open, now),
+                item("b", State::Open, then),
+            ],
+            failed: vec!["not fetched".to_string()],
+        };
+
+        let outcomes = compare(tracked, refreshed, Duration::zero(), now);
+
+        assert_eq!(outcomes.len(), 3);
+        assert!(matches!(&outcomes[0].1, Outcome::Update(i, _) if i.foreign_id == "a"));
+        assert!(matches!(&outcomes[1].1, Outcome::Ignore));
+        match &outcomes[2].1 {
+            Outcome::Remove(i, changes) => {
+                assert_eq!(i.foreign_id, "c");
+                assert!(changes.iter().any(|c| matches!(c, Change::State { .. })));
+            }
+            _ => panic!("c should have been removed"),
+        }
+    }
+
+    fn repo(id: i32, title: &str, foreign_id: Option<&str>) -> FullStoredRepo {
+        FullStoredRepo {
+            id,
+            title: title.to_string(),
+            foreign_id: foreign_id.map(String::from),
+            renamed_from: None,
+            renamed_at: None,
+            last_commit: None,
+            branch_health: None,
+            issues: vec![],
+            prs: vec![],
+            releases: vec![],
+            discussions: vec![],
+        }
+    }
+
+    fn remote(foreign_id: &str, name: &str) -> RemoteRepo {
+        RemoteRepo {
+            foreign_id: foreign_id.to_string(),
+            name: RepoName::from(name).unwrap(),
+        }
+    }
+
+    #[test]
+    fn notices_repos_that_were_renamed_or_transferred() {
+        let repos = vec![
+            repo(1, "foo/bar", Some("repo-1")),
+            repo(2, "foo/baz", Some("repo-2")),
+            repo(3, "foo/gone", Some("repo-3")),
+            repo(4, "foo/unknown", None),
+        ];
+        let remote = vec![
+            remote("repo-2", "other-org/baz"),
+            remote("repo-1", "foo/bar"),
+        ];
+
+        let renames = renames(&repos, remote);
+
+        assert_eq!(renames.len(), 1);
+        assert_eq!(renames[0].0.id, 2);
+        assert_eq!(renames[0].1, "other-org/baz");
+    }
+
+    fn resolved(foreign_id: &str, state: State, closed_at: DateTime<Utc>) -> NewTrackedItem {
+        NewTrackedItem {
+            closed_at: Some(closed_at),
+            closed_by: Some(Author::new("octocat")),
+            ..item(foreign_id, state, closed_at)
+        }
+    }
+
+    #[test]
+    fn keeps_merged_prs_around_for_a_while() {
+        let then = Utc::now() - Duration::hours(1);
+        let now = Utc::now();
+
+        let outcome = update(
+            item("a", State::Open, then),
+            resolved("a", State::Merged, now),
+            Duration::hours(24),
+            now,
+        );
+
+        assert!(
+            matches!(outcome, Outcome::Update(i, _) if i.state == State::Merged && i.closed_by.is_some())
+        );
+    }
+
+    #[test]
+    fn removes_resolved_items_once_they_were_kept_long_enough() {
+        let now = Utc::now();
+        let closed = now - Duration::hours(25);
+
+        let outcome = update(
+            resolved("a", State::Closed, closed),
+            resolved("a", State::Closed, closed),
+            Duration::hours(24),
+            now,
+        );
+
+        assert!(matches!(outcome, Outcome::Remove(_, changes) if changes.is_empty()));
+    }
+
+    #[test]
+    fn remembers_how_an_item_was_resolved_even_when_it_goes_right_away() {
+        let now = Utc::now();
+        let closed = now - Duration::hours(25);
+
+        let outcome = update(
+            item("a", State::Open, closed),
+            resolved("a", State::Closed, closed),
+            Duration::hours(24),
+            now,
+        );
+
+        match outcome {
+            Outcome::Remove(_, changes) => assert!(matches!(
+                changes.as_slice(),
+                [Change::State {
+                    from: State::Open,
+                    to: State::Closed
+                }]
+            )),
+            _ => panic!("the item should have been removed"),
+        }
+    }
+
+    #[test]
+    fn leaves_recently_resolved_items_alone() {
+        let now = Utc::now();
+        let closed = now - Duration::hours(2);
+
+        let outcome = update(
+            resolved("a", State::Closed, closed),
+            resolved("a", State::Closed, closed),
+            Duration::hours(24),
+            now,
+        );
+
+        assert!(matches!(outcome, Outcome::Ignore));
+    }
+
+    #[test]
+    fn notices_when_a_draft_becomes_ready() {
+        let then = Utc::now() - Duration::hours(1);
+
+        let outcome = update(
+            item("a", State::Draft, then),
+            item("a", State::Open, then),
+            Duration::hours(24),
+            Utc::now(),
+        );
+
+        assert!(matches!(outcome, Outcome::Update(i, _) if i.state == State::Open));
+    }
+
+    #[test]
+    fn lists_everything_that_changed_about_an_item() {
+        let then = Utc::now() - Duration::hours(1);
+        let now = Utc::now();
+
+        let mut old = item("a", State::Open, then);
+        old.labels = vec![Label::from("bug"), Label::from("help wanted")];
+        let mut new = item("a", State::Closed, now);
+        new.title = "some other PR".to_string();
+        new.labels = vec![Label::from("wontfix"), Label::from("bug")];
+
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                Change::Title {
+                    from: "some PR".to_string(),
+                    to: "some other PR".to_string()
+                },
+                Change::LabelAdded("wontfix".to_string()),
+                Change::LabelRemoved("help wanted".to_string()),
+                Change::State {
+                    from: State::Open,
+                    to: State::Closed
+                },
+                Change::Updated {
+                    from: then,
+                    to: now
+                },
+            ]
+        );
+        assert!(changes(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn notices_when_reviews_come_in() {
+        let then = Utc::now() - Duration::hours(1);
+
+        let mut old = item("a", State::Open, then);
+        old.details = ItemDetails::PR {
+            reviews: Reviews {
+                decision: Some(ReviewDecision::ReviewRequired),
+                requested: vec!["hubot".to_string()],
+                latest: vec![],
+            },
+            checks: Checks::default(),
+        };
+        let mut new = item("a", State::Open, then);
+        new.details = ItemDetails::PR {
+            reviews: Reviews {
+                decision: Some(ReviewDecision::Approved),
+                requested: vec!["octocat".to_string()],
+                latest: vec![
+                    Review {
+                        by: "hubot".to_string(),
+                        state: ReviewState::Approved,
+                        submitted_at: Some(then),
+                    },
+                    Review {
+                        by: "someone".to_string(),
+                        state: ReviewState::Pending,
+                        submitted_at: None,
+                    },
+                ],
+            },
+            checks: Checks::default(),
+        };
+
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                Change::ReviewDecision {
+                    from: Some(ReviewDecision::ReviewRequired),
+                    to: Some(ReviewDecision::Approved),
+                },
+                Change::ReviewRequested("octocat".to_string()),
+                Change::Reviewed {
+                    by: "hubot".to_string(),
+                    state: ReviewState::Approved,
+                },
+            ]
+        );
+        assert!(matches!(
+            update(old, new, Duration::hours(24), Utc::now()),
+            Outcome::Update(_, _)
+        ));
+    }
+
+    #[test]
+    fn notices_when_review_requests_are_withdrawn() {
+        let then = Utc::now() - Duration::hours(1);
+        let pr = |requested: &[&str]| {
+            let mut pr = item("a", State::Open, then);
+            pr.details = ItemDetails::PR {
+                reviews: Reviews {
+                    decision: Some(ReviewDecision::ReviewRequired),
+                    requested: requested.iter().map(|r| r.to_string()).collect(),
+                    latest: vec![],
+                },
+                checks: Checks::default(),
+            };
+            pr
+        };
+
+        assert_eq!(
+            changes(&pr(&["hubot", "octocat"]), &pr(&["octocat"])),
+            vec![Change::ReviewRequestRemoved("hubot".to_string())]
+        );
+    }
+
+    fn commit(ci_status: Option<CiStatus>) -> Commit {
+        Commit {
+            branch: "main".into(),
+            sha: "8a2f1e0".into(),
+            headline: "Speed up the build".into(),
+            author: "octocat".into(),
+            committed_at: Utc::now(),
+            ci_status,
+        }
+    }
+
+    #[test]
+    fn notices_when_the_default_branch_turns_red_or_green() {
+        let then = Utc::now() - Duration::hours(1);
+        let now = Utc::now();
+        let green = BranchHealth {
+            status: CiStatus::Passing,
+            since: Some(then),
+        };
+        let red = BranchHealth {
+            status: CiStatus::Failing,
+            since: Some(now),
+        };
+
+        assert_eq!(
+            branch_health(Some(&green), &commit(Some(CiStatus::Failing)), now),
+            Some(red.clone())
+        );
+        assert_eq!(
+            branch_events(Some(&green), &red, now),
+            vec![RepoEvent {
+                change: RepoChange::BranchHealth {
+                    from: CiStatus::Passing,
+                    to: CiStatus::Failing,
+                },
+                recorded_at: now,
+            }]
+        );
+    }
+
+    #[test]
+    fn does_not_know_since_when_a_branch_first_seen_is_green() {
+        let now = Utc::now();
+        let green = BranchHealth {
+            status: CiStatus::Passing,
+            since: None,
+        };
+
+        assert_eq!(
+            branch_health(None, &commit(Some(CiStatus::Passing)), now),
+            Some(green.clone())
+        );
+        assert!(branch_events(None, &green, now).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_branch_health_while_nothing_turns_red_or_green() {
+        let then = Utc::now() - Duration::hours(1);
+        let now = Utc::now();
+        let green = BranchHealth {
+            status: CiStatus::Passing,
+            since: Some(then),
+        };
+
+        assert_eq!(
+            branch_health(Some(&green), &commit(Some(CiStatus::Passing)), now),
+            None
+        );
+        assert_eq!(
+            branch_health(Some(&green), &commit(Some(CiStatus::Pending)), now),
+            None
+        );
+        assert_eq!(branch_health(Some(&green), &commit(None), now), None);
+    }
+
+    #[test]
+    fn notices_when_ci_goes_red() {
+        let then = Utc::now() - Duration::hours(1);
+
+        let mut old = item("a", State::Open, then);
+        old.details = ItemDetails::PR {
+            reviews: Reviews::default(),
+            checks: Checks {
+                status: Some(CiStatus::Passing),
+                failing: vec![],
+            },
+        };
+        let mut new = item("a", State::Open, then);
+        new.details = ItemDetails::PR {
+            reviews: Reviews::default(),
+            checks: Checks {
+                status: Some(CiStatus::Failing),
+                failing: vec!["build".to_string()],
+            },
+        };
+
+        assert_eq!(
+            changes(&old, &new),
+            vec![Change::Ci {
+                from: Some(CiStatus::Passing),
+                to: Some(CiStatus::Failing),
+            }]
+        );
+    }
+
+    #[test]
+    fn notices_when_a_discussion_gets_answered() {
+        let then = Utc::now() - Duration::hours(1);
+        let now = Utc::now();
+
+        let discussion = |answered, last_updated| NewTrackedItem {
+            details: ItemDetails::Discussion(Discussion {
+                category: "Q&A".into(),
+                answered,
+                comments: 3,
+            }),
+            ..item("a", State::Open, last_updated)
+        };
+
+        assert_eq!(
+            changes(&discussion(false, then), &discussion(true, now)),
+            vec![
+                Change::Answered,
+                Change::Updated {
+                    from: then,
+                    to: now
+                }
+            ]
+        );
+        assert!(changes(&discussion(true, then), &discussion(true, then)).is_empty());
+    }
+
+    #[test]
+    fn takes_the_timeline_from_the_refreshed_item() {
+        let then = Utc::now() - Duration::hours(1);
+        let now = Utc::now();
+
+        let old = item("a", State::Open, then);
+        let mut new = item("a", State::Open, now);
+        new.timeline = vec![TimelineEvent {
+            kind: TimelineEventKind::Commented,
+            by: "hubot".to_string(),
+            at: now,
+            detail: None,
+        }];
+
+        let outcome = update(old, new.clone(), Duration::hours(24), now);
+        assert!(matches!(outcome, Outcome::Update(i, _) if i.timeline == new.timeline));
+    }
+
+    #[test]
+    fn stores_which_checks_fail_even_when_ci_stays_red() {
+        let then = Utc::now() - Duration::hours(1);
+        let failing = |names: &[&str]| {
+            let mut pr = item("a", State::Open, then);
+            pr.details = ItemDetails::PR {
+                reviews: Reviews::default(),
+                checks: Checks {
+                    status: Some(CiStatus::Failing),
+                    failing: names.iter().map(|n| n.to_string()).collect(),
+                },
+            };
+            pr
+        };
+
+        let outcome = update(
+            failing(&["build"]),
+            failing(&["build", "lint"]),
+            Duration::hours(24),
+            Utc::now(),
+        );
+
+        match outcome {
+            Outcome::Update(pr, changes) => {
+                assert!(changes.is_empty());
+                assert_eq!(pr.details.checks().unwrap().failing, vec!["build", "lint"]);
+            }
+            _ => panic!("the failing checks should have been stored"),
+        }
+    }
+
+    #[test]
+    fn does_not_care_about_the_order_of_labels() {
+        let then = Utc::now() - Duration::hours(1);
+
+        let mut old = item("a", State::Open, then);
+        old.labels = vec![Label::from("bug"), Label::from("help wanted")];
+        let mut new = item("a", State::Open, then);
+        new.labels = vec![Label::from("help wanted"), Label::from("bug")];
+
+        assert!(changes(&old, &new).is_empty());
+        assert!(matches!(
+            update(old, new, Duration::hours(24), Utc::now()),
+            Outcome::Ignore
+        ));
+    }
+
+    #[test]
+    fn does_not_compare_truncated_labels() {
+        let then = Utc::now() - Duration::hours(1);
+
+        let mut old = item("a", State::Open, then);
+        old.labels = vec![Label::from("bug")];
+        old.labels_truncated = true;
+        let mut new = item("a", State::Open, then);
+        new.labels = vec![Label::from("help wanted")];
+        new.labels_truncated = true;
+
+        assert!(changes(&old, &new).is_empty());
+    }
+
+    fn release(
+        foreign_id: &str,
+        tag: &str,
+        prerelease: bool,
+        published_at: DateTime<Utc>,
+    ) -> NewTrackedItem {
+        NewTrackedItem {
+            title: tag.to_string(),
+            details: ItemDetails::Release(Release {
+                tag: tag.to_string(),
+                prerelease,
+                published_at,
+            }),
+            ..item(foreign_id, State::Open, published_at)
+        }
+    }
+
+    #[test]
+    fn notices_new_and_promoted_releases() {
+        let then = Utc::now() - Duration::days(7);
+        let now = Utc::now();
+
+        let stored = vec![
+            release("r2", "v2.0.0-rc.1", true, then),
+            release("r1", "v1.0.0", false, then - Duration::days(30)),
+        ];
+        let latest = vec![
+            release("r3", "v2.0.1-rc.1", true, now),
+            release("r2", "v2.0.0-rc.1", false, then),
+            release("r1", "v1.0.0", false, then - Duration::days(30)),
+        ];
+
+        let changes = release_events(&stored, &latest, now)
+            .into_iter()
+            .map(|event| (event.foreign_id, event.change))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            vec![
+                ("r3".to_string(), Change::Prereleased("v2.0.1-rc.1".into())),
+                ("r2".to_string(), Change::Released("v2.0.0-rc.1".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_first_releases_of_a_repo_are_not_news() {
+        let now = Utc::now();
+        let latest = vec![release("r1", "v1.0.0", false, now)];
+
+        assert!(release_events(&[], &latest, now).is_empty());
+    }
+
+    #[test]
+    fn drops_releases_that_are_no_longer_among_the_latest() {
+        let now = Utc::now();
+        let stored = vec![
+            release("r2", "v2.0.0", false, now),
+            release("r1", "v1.0.0", false, now - Duration::days(30)),
+        ];
+        let latest = vec![
+            release("r3", "v3.0.0", false, now),
+            release("r2", "v2.0.0", false, now),
+        ];
+
+        let stale = stale_releases(&stored, &latest);
+
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].foreign_id, "r1");
+    }
+
+    fn rate_limit(remaining: i64, reset_at: DateTime<Utc>) -> Option<RateLimit> {
+        Some(RateLimit {
+            limit: 5000,
+            cost: 1,
+            remaining,
+            reset_at,
+        })
+    }
+
+    #[test]
+    fn forgets_untracked_items_once_they_are_tracked_again() {
+        let untracked = Untracked::default();
+        let shared = untracked.clone();
+
+        shared.insert("a");
+        assert!(untracked.contains("a"));
+        assert!(!untracked.contains("b"));
+
+        shared.remove("a");
+        assert!(!untracked.contains("a"));
+    }
+
+    #[test]
+    fn forgets_untracked_items_once_a_pass_no_longer_has_them() {
+        let untracked = Untracked::default();
+        untracked.insert("a");
+        untracked.insert("b");
+
+        untracked.keep_only(&["b".to_string(), "c".to_string()]);
+
+        assert!(!untracked.contains("a"));
+        assert!(untracked.contains("b"));
+        assert!(!untracked.contains("c"));
+    }
+
+    #[test]
+    fn keeps_going_while_there_is_budget_left() {
+        let now = Utc::now();
+
+        assert_eq!(pause_for(None, 100, now), None);
+        assert_eq!(
+            pause_for(rate_limit(101, now + Duration::minutes(10)), 100, now),
+            None
+        );
+    }
+
+    #[test]
+    fn pauses_until_the_reset_when_down_to_the_reserve() {
+        let now = Utc::now();
+
+        let pause = pause_for(rate_limit(100, now + Duration::minutes(10)), 100, now);
+
+        assert_eq!(pause, Some(std::time::Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn does_not_pause_once_the_reset_has_passed() {
+        let now = Utc::now();
+
+        let pause = pause_for(rate_limit(0, now - Duration::minutes(1)), 100, now);
+
+        assert_eq!(pause, None);
+    }
+}
@@ -1,12 +1,62 @@
 #![allow(proc_macro_derive_resolution_fallback)]
 use crate::domain;
 use crate::BetterOption;
-use anyhow::{bail, Result};
+use anyhow::Result;
 use async_std::task;
+use async_trait::async_trait;
 use graphql_client::GraphQLQuery;
+use rand::Rng;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::time::Duration;
+use surf::http::header::{HeaderName, HeaderValue};
+use surf::middleware::{HttpClient, Middleware, Next, Request, Response};
 
 type DateTime = chrono::DateTime<chrono::Utc>;
 type URI = String;
+type GitObjectID = String;
+
+/// Every query gets its own type for the kind of actor, so this turns any of them into an author.
+macro_rules! author {
+    ($author:expr, $($on:ident)::+) => {
+        $author
+            .map(|a| {
+                let kind = match a.on {
+                    $($on)::+::Bot => domain::AuthorKind::Bot,
+                    $($on)::+::Organization => domain::AuthorKind::Organization,
+                    _ => domain::AuthorKind::User,
+                };
+
+                domain::Author::new(a.login)
+                    .with_link(a.url)
+                    .with_avatar(a.avatar_url)
+                    .with_kind(kind)
+            })
+            .unwrap_or(domain::Author::new("ghost").with_link("https://github.com/ghost"))
+    };
+}
+
+/// The labels of an item, and whether there were more than we asked for.
+macro_rules! labels {
+    ($connection:expr) => {
+        match $connection {
+            Some(connection) => {
+                let labels: Vec<_> = funky_flatten(connection.nodes)
+                    .into_iter()
+                    .map(|s| domain::Label {
+                        name: s.name,
+                        color: Some(s.color),
+                        description: s.description,
+                    })
+                    .collect();
+                let truncated = connection.total_count as usize > labels.len();
+                (labels, truncated)
+            }
+            None => (vec![], false),
+        }
+    };
+}
 
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -40,50 +90,793 @@ pub struct BroadRepoView;
 )]
 pub struct RepoExistsView;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema.graphql",
+    query_path = "graphql/nodes.graphql",
+    response_derives = "Debug"
+)]
+pub struct NodesView;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema.graphql",
+    query_path = "graphql/repo-nodes.graphql",
+    response_derives = "Debug"
+)]
+pub struct RepoNodesView;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema.graphql",
+    query_path = "graphql/issue-with-activity.graphql",
+    response_derives = "Debug"
+)]
+pub struct IssueWithActivity;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema.graphql",
+    query_path = "graphql/last-commits.graphql",
+    response_derives = "Debug"
+)]
+pub struct LastCommitsView;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema.graphql",
+    query_path = "graphql/releases.graphql",
+    response_derives = "Debug"
+)]
+pub struct ReleasesView;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema.graphql",
+    query_path = "graphql/discussion.graphql",
+    response_derives = "Debug"
+)]
+pub struct DiscussionView;
+
+/// GitHub does not hand out more than 100 nodes of a connection per request.
+const PAGE_SIZE: usize = 100;
+
+/// How many PRs and how many issues the broad repo view fetches, unless configured otherwise.
+pub const DEFAULT_MAX_ITEMS: usize = 500;
+
+/// How many labels are fetched per issue or PR, unless configured otherwise.
+pub const DEFAULT_MAX_LABELS: usize = 25;
+
+/// How many timeline events are fetched per issue or PR, unless configured otherwise.
+pub const DEFAULT_TIMELINE_EVENTS: usize = 5;
+
+/// How many of the latest releases are kept per repo, unless configured otherwise.
+pub const DEFAULT_MAX_RELEASES: usize = 3;
+
+/// Comments are cut down to this many characters.
+const EXCERPT_LENGTH: usize = 140;
+
+/// The API base of github.com. GitHub Enterprise Server lives under `https://<host>/api`.
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
 pub struct GithubClient {
     token: String,
+    graphql_url: String,
+    headers: ExtraHeaders,
+    max_items: usize,
+    max_labels: usize,
+    timeline_events: usize,
+    max_releases: usize,
+    backoff: Backoff,
+    rate_limit: Mutex<Option<domain::RateLimit>>,
+}
+
+/// How often and how patiently transient failures are retried.
+pub struct Backoff {
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled for every one after.
+    pub base_delay: Duration,
+    /// Never wait longer than this. When GitHub asks for a longer wait, the request is
+    /// given up on as transient instead of being retried early.
+    pub max_delay: Duration,
+    /// A single attempt that takes longer than this is given up on and counts as transient.
+    pub timeout: Duration,
+}
+
+impl Backoff {
+    /// Exponential backoff with jitter, so that retries of parallel requests spread out.
+    fn delay(&self, attempt: u32) -> Duration {
+        let base = self.base_delay.as_millis() as u64;
+        let exponential = 2u64
+            .checked_pow(attempt)
+            .map_or(u64::MAX, |factor| base.saturating_mul(factor));
+        let capped = std::cmp::min(exponential, self.max_delay.as_millis() as u64);
+
+        Duration::from_millis(rand::thread_rng().gen_range(capped / 2, capped + 1))
+    }
+}
+
+impl std::default::Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Configured headers, already validated. surf's own `set_header` only takes `'static` names,
+/// so these are added to the underlying request instead.
+#[derive(Clone, Default)]
+struct ExtraHeaders(Vec<(HeaderName, HeaderValue)>);
+
+impl<C: HttpClient> Middleware<C> for ExtraHeaders {
+    fn handle<'a>(
+        &'a self,
+        mut req: Request,
+        client: C,
+        next: Next<'a, C>,
+    ) -> futures::future::BoxFuture<'a, std::result::Result<Response, surf::Exception>> {
+        for (name, value) in self.0.iter() {
+            req.headers_mut().insert(name.clone(), value.clone());
+        }
+        next.run(req, client)
+    }
+}
+
+/// What went wrong with a single attempt at a request.
+enum Failure {
+    Transient {
+        reason: String,
+        retry_after: Option<Duration>,
+    },
+    NotFound(String),
+    Permanent(String),
+}
+
+/// Unlike `graphql_client::Response`, this keeps the `type` GitHub attaches to its errors.
+#[derive(serde::Deserialize)]
+struct GraphqlResponse {
+    data: Option<serde_json::Value>,
+    errors: Option<Vec<GraphqlError>>,
+}
+
+#[derive(serde::Deserialize)]
+struct GraphqlError {
+    message: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    path: Vec<serde_json::Value>,
+}
+
+impl GraphqlError {
+    /// A deleted or inaccessible node asked for through `nodes(ids:)` comes back as `null`,
+    /// along with one of these. The rest of the nodes are still good.
+    fn is_missing_node(&self) -> bool {
+        self.kind.as_deref() == Some("NOT_FOUND")
+            && self.path.first().and_then(|p| p.as_str()) == Some("nodes")
+    }
+}
+
+impl Failure {
+    fn transient<S: ToString>(reason: S) -> Self {
+        Failure::Transient {
+            reason: reason.to_string(),
+            retry_after: None,
+        }
+    }
+}
+
+/// How long a `Retry-After` header asks us to wait. It holds either a number of seconds or
+/// an HTTP date, which is in the format of RFC 2822.
+fn retry_after(value: &str, now: DateTime) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    // A date in the past means right away
+    Some(
+        (at.with_timezone(&chrono::Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// The `rateLimit` every query asks for alongside its actual data.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RateLimitView {
+    limit: i64,
+    cost: i64,
+    remaining: i64,
+    reset_at: DateTime,
 }
 
 impl GithubClient {
     pub fn new<S: Into<String>>(token: S) -> Self {
         GithubClient {
             token: token.into(),
+            graphql_url: format!("{}/graphql", DEFAULT_API_URL),
+            headers: ExtraHeaders::default(),
+            max_items: DEFAULT_MAX_ITEMS,
+            max_labels: DEFAULT_MAX_LABELS,
+            timeline_events: DEFAULT_TIMELINE_EVENTS,
+            max_releases: DEFAULT_MAX_RELEASES,
+            backoff: Backoff::default(),
+            rate_limit: Mutex::new(None),
         }
     }
 
-    fn make_request<Q: serde::Serialize, R: serde::de::DeserializeOwned>(
+    /// Points the client at a different API, such as a GitHub Enterprise Server or a local fake.
+    pub fn with_api_url<S: Into<String>>(mut self, api_url: S) -> Self {
+        self.graphql_url = format!("{}/graphql", api_url.into().trim_end_matches('/'));
+        self
+    }
+
+    /// Sends an additional header along with every request. Fails on names or values that
+    /// are not valid in HTTP.
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| anyhow::anyhow!("invalid header name {:?}: {}", name, e))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| anyhow::anyhow!("invalid value for header {}: {}", name, e))?;
+        self.headers.0.push((name, value));
+        Ok(self)
+    }
+
+    /// Caps the number of PRs, of issues and of discussions `entire_repo` pages through.
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = max_items;
+        self
+    }
+
+    /// Caps the number of labels fetched per item. Items with more are marked as truncated.
+    pub fn with_max_labels(mut self, max_labels: usize) -> Self {
+        self.max_labels = std::cmp::min(max_labels, PAGE_SIZE);
+        self
+    }
+
+    /// Caps the number of timeline events fetched per item.
+    pub fn with_timeline_events(mut self, timeline_events: usize) -> Self {
+        self.timeline_events = std::cmp::min(timeline_events, PAGE_SIZE);
+        self
+    }
+
+    /// Caps the number of releases kept per repo.
+    pub fn with_max_releases(mut self, max_releases: usize) -> Self {
+        self.max_releases = std::cmp::min(max_releases, PAGE_SIZE);
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sends the query, retrying transient failures with exponential backoff.
+    async fn make_request<Q: serde::Serialize, R: serde::de::DeserializeOwned>(
         &self,
         query: Q,
     ) -> Result<R> {
-        task::block_on(async {
-            let mut response = match surf::post("https://api.github.com/graphql")
-                .set_header("Authorization", format!("Bearer {}", self.token))
-                .body_json(&query)
-                .unwrap()
+        let mut attempt = 0;
+
+        loop {
+            let (reason, retry_after) = match self.attempt_request(&query).await {
+                Ok(data) => return Result::Ok(data),
+                Err(Failure::NotFound(reason)) => {
+                    return Err(domain::ClientError::NotFound(reason).into())
+                }
+                Err(Failure::Permanent(reason)) => {
+                    return Err(domain::ClientError::Permanent(reason).into())
+                }
+                Err(Failure::Transient {
+                    reason,
+                    retry_after,
+                }) => (reason, retry_after),
+            };
+
+            if attempt >= self.backoff.max_retries {
+                return Err(domain::ClientError::Transient(reason).into());
+            }
+
+            let delay = match retry_after {
+                // Asking again before GitHub allows it only makes its secondary rate limit stricter
+                Some(wait) if wait > self.backoff.max_delay => {
+                    return Err(domain::ClientError::Transient(format!(
+                        "{}, and GitHub asked to wait {:?} before trying again",
+                        reason, wait
+                    ))
+                    .into())
+                }
+                Some(wait) => wait,
+                None => self.backoff.delay(attempt),
+            };
+
+            log::warn!(
+                "request to GitHub failed, retrying in {:?}: {}",
+                delay,
+                reason
+            );
+            task::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn attempt_request<Q: serde::Serialize, R: serde::de::DeserializeOwned>(
+        &self,
+        query: &Q,
+    ) -> std::result::Result<R, Failure> {
+        let request = surf::post(&self.graphql_url)
+            .set_header("Authorization", format!("Bearer {}", self.token))
+            .middleware(self.headers.clone())
+            .body_json(query)
+            .map_err(|e| Failure::Permanent(e.to_string()))?;
+
+        let mut response = match async_std::future::timeout(self.backoff.timeout, request).await {
+            Ok(Ok(r)) => r,
+            Ok(Err(err)) => return Err(Failure::transient(err)),
+            Err(_) => {
+                return Err(Failure::transient(format!(
+                    "no response within {:?}",
+                    self.backoff.timeout
+                )))
+            }
+        };
+
+        let status = response.status();
+        if status != 200 {
+            let retry_after = response
+                .header("Retry-After")
+                .and_then(|value| retry_after(value, chrono::Utc::now()));
+
+            let http_error = response
+                .body_string()
                 .await
-            {
-                Ok(r) => r,
-                Err(err) => bail!(err),
+                .unwrap_or_else(|e| e.to_string());
+            let reason = format!("Did not get a positive response: {}", http_error);
+
+            // GitHub's secondary rate limits answer with 403 or 429 and a `Retry-After`
+            return match (status.as_u16(), retry_after) {
+                (500..=599, _) => Err(Failure::transient(reason)),
+                (403, Some(_)) | (429, Some(_)) => Err(Failure::Transient {
+                    reason,
+                    retry_after,
+                }),
+                _ => Err(Failure::Permanent(reason)),
             };
+        };
 
-            if response.status() != 200 {
-                match response.body_string().await {
-                    Ok(http_error) => bail!("Did not get a positive response: {}", http_error),
-                    Err(e) => bail!(e),
-                };
+        // Parsed loosely first so the rate limit can be picked out of any query.
+        // A body that got cut off on the way is worth another try.
+        let inner: GraphqlResponse = response.body_json().await.map_err(Failure::transient)?;
+
+        if let Some(rate_limit) = inner
+            .data
+            .as_ref()
+            .and_then(|data| data.get("rateLimit"))
+            .and_then(|raw| serde_json::from_value::<RateLimitView>(raw.clone()).ok())
+        {
+            self.remember(rate_limit);
+        }
+
+        let has_data = inner.data.is_some();
+        if let Some(error) = inner
+            .errors
+            .unwrap_or_default()
+            .into_iter()
+            .find(|error| !(has_data && error.is_missing_node()))
+        {
+            return match error.kind.as_deref() {
+                Some("NOT_FOUND") => Err(Failure::NotFound(error.message)),
+                // The primary rate limit, which is only good again once it resets
+                Some("RATE_LIMITED") => Err(Failure::Transient {
+                    reason: error.message,
+                    retry_after: self.until_reset(),
+                }),
+                _ => Err(Failure::Permanent(error.message)),
             };
+        }
+
+        match inner.data {
+            Some(valuable_stuff) => serde_json::from_value(valuable_stuff)
+                .map_err(|e| Failure::Permanent(e.to_string())),
+            None => Err(Failure::Permanent("There was no data in response".into())),
+        }
+    }
 
-            let inner: graphql_client::Response<R> = response.body_json().await?;
+    /// How long until the rate limit resets, as far as the last query told us.
+    fn until_reset(&self) -> Option<Duration> {
+        let rate_limit = self.rate_limit.lock().unwrap();
+        (rate_limit.as_ref()?.reset_at - chrono::Utc::now())
+            .to_std()
+            .ok()
+    }
+
+    fn remember(&self, view: RateLimitView) {
+        let RateLimitView {
+            limit,
+            cost,
+            remaining,
+            reset_at,
+        } = view;
+
+        log::debug!(
+            "query cost {}, {} of {} points left",
+            cost,
+            remaining,
+            limit
+        );
+
+        *self.rate_limit.lock().unwrap() = Some(domain::RateLimit {
+            limit,
+            cost,
+            remaining,
+            reset_at,
+        });
+    }
+}
+
+/// Every query spreading the `ReviewStatus` fragment gets its own copy of its types.
+macro_rules! reviews_from_fragment {
+    ($($query:ident),+) => {
+        $(
+            impl From<$query::ReviewStatus> for domain::Reviews {
+                fn from(status: $query::ReviewStatus) -> Self {
+                    use $query::ReviewStatusReviewRequestsNodesRequestedReviewer as Reviewer;
+
+                    let decision = match status.review_decision {
+                        Some($query::PullRequestReviewDecision::APPROVED) => {
+                            Some(domain::ReviewDecision::Approved)
+                        }
+                        Some($query::PullRequestReviewDecision::CHANGES_REQUESTED) => {
+                            Some(domain::ReviewDecision::ChangesRequested)
+                        }
+                        Some($query::PullRequestReviewDecision::REVIEW_REQUIRED) => {
+                            Some(domain::ReviewDecision::ReviewRequired)
+                        }
+                        _ => None,
+                    };
+
+                    let requested = funky_flatten(status.review_requests.and_then(|r| r.nodes))
+                        .into_iter()
+                        .filter_map(|request| match request.requested_reviewer? {
+                            Reviewer::User(user) => Some(user.login),
+                            Reviewer::Team(team) => Some(team.name),
+                            Reviewer::Mannequin(mannequin) => Some(mannequin.login),
+                        })
+                        .collect();
+
+                    let latest = funky_flatten(status.latest_reviews.and_then(|r| r.nodes))
+                        .into_iter()
+                        .map(|review| domain::Review {
+                            by: review
+                                .author
+                                .map(|a| a.login)
+                                .unwrap_or_else(|| "ghost".to_string()),
+                            state: match review.state {
+                                $query::PullRequestReviewState::APPROVED => {
+                                    domain::ReviewState::Approved
+                                }
+                                $query::PullRequestReviewState::CHANGES_REQUESTED => {
+                                    domain::ReviewState::ChangesRequested
+                                }
+                                $query::PullRequestReviewState::DISMISSED => {
+                                    domain::ReviewState::Dismissed
+                                }
+                                $query::PullRequestReviewState::PENDING => {
+                                    domain::ReviewState::Pending
+                                }
+                                _ => domain::ReviewState::Commented,
+                            },
+                            submitted_at: review.submitted_at,
+                        })
+                        .collect();
+
+                    domain::Reviews {
+                        decision,
+                        requested,
+                        latest,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+reviews_from_fragment!(broad_repo_view, nodes_view, pull_request_view);
+
+macro_rules! checks_from_fragment {
+    ($($query:ident),+) => {
+        $(
+            impl From<$query::StatusChecks> for domain::Checks {
+                fn from(ci: $query::StatusChecks) -> Self {
+                    use $query::StatusChecksCommitsNodesCommitStatusCheckRollupContextsNodes as Context;
 
-            if let Some(errors) = inner.errors {
-                bail!(errors[0].message.clone());
+                    // Only the head commit is asked for, so there is at most one
+                    let rollup = match funky_flatten(ci.commits.nodes)
+                        .into_iter()
+                        .next()
+                        .and_then(|node| node.commit.status_check_rollup)
+                    {
+                        Some(rollup) => rollup,
+                        None => return domain::Checks::default(),
+                    };
+
+                    let status = match rollup.state {
+                        $query::StatusState::SUCCESS => domain::CiStatus::Passing,
+                        $query::StatusState::FAILURE | $query::StatusState::ERROR => {
+                            domain::CiStatus::Failing
+                        }
+                        _ => domain::CiStatus::Pending,
+                    };
+
+                    let failing = funky_flatten(rollup.contexts.nodes)
+                        .into_iter()
+                        .filter_map(|context| match context {
+                            Context::CheckRun(run) => match run.conclusion {
+                                Some($query::CheckConclusionState::FAILURE)
+                                | Some($query::CheckConclusionState::TIMED_OUT)
+                                | Some($query::CheckConclusionState::CANCELLED)
+                                | Some($query::CheckConclusionState::ACTION_REQUIRED)
+                                | Some($query::CheckConclusionState::STARTUP_FAILURE) => {
+                                    Some(run.name)
+                                }
+                                _ => None,
+                            },
+                            Context::StatusContext(status) => match status.state {
+                                $query::StatusState::FAILURE | $query::StatusState::ERROR => {
+                                    Some(status.context)
+                                }
+                                _ => None,
+                            },
+                        })
+                        .collect();
+
+                    domain::Checks {
+                        status: Some(status),
+                        failing,
+                    }
+                }
             }
+        )+
+    };
+}
+
+checks_from_fragment!(broad_repo_view, nodes_view, pull_request_view);
+
+/// Every query spreading the `IssueDetails` fragment gets its own copy of its types.
+macro_rules! issue_from_fragment {
+    ($($query:ident),+) => {
+        $(
+            impl From<$query::IssueDetails> for domain::NewTrackedItem {
+                fn from(issue: $query::IssueDetails) -> Self {
+                    use $query::IssueDetailsTimelineItemsNodes as Event;
+
+                    let (labels, labels_truncated) = labels!(issue.labels);
+
+                    let state = match issue.state {
+                        $query::IssueState::OPEN => domain::State::Open,
+                        _ => domain::State::Closed,
+                    };
 
-            match inner.data {
-                Some(valuable_stuff) => Result::Ok(valuable_stuff),
-                None => bail!("There was no data in response"),
+                    let closed_by = funky_flatten(issue.timeline_items.nodes)
+                        .into_iter()
+                        .filter_map(|event| match event {
+                            Event::ClosedEvent(closed) => closed.actor,
+                            _ => None,
+                        })
+                        .map(|a| domain::Author::new(a.login).with_link(a.url))
+                        .next_back();
+
+                    domain::NewTrackedItem {
+                        state,
+                        foreign_id: issue.id,
+                        title: issue.title,
+                        link: issue.url,
+                        by: author!(issue.author, $query::IssueDetailsAuthorOn),
+                        labels,
+                        labels_truncated,
+                        timeline: vec![],
+                        details: domain::ItemDetails::Issue,
+                        last_updated: issue.updated_at,
+                        number: issue.number as i32,
+                        closed_at: issue.closed_at,
+                        closed_by,
+                    }
+                }
             }
-        })
+        )+
+    };
+}
+
+issue_from_fragment!(broad_repo_view, issue_view, nodes_view);
+
+macro_rules! pull_request_from_fragment {
+    ($($query:ident),+) => {
+        $(
+            impl From<$query::PullRequestDetails> for domain::NewTrackedItem {
+                fn from(pr: $query::PullRequestDetails) -> Self {
+                    use $query::PullRequestDetailsTimelineItemsNodes as Event;
+
+                    let (labels, labels_truncated) = labels!(pr.labels);
+
+                    let state = match pr.state {
+                        $query::PullRequestState::OPEN if pr.is_draft => domain::State::Draft,
+                        $query::PullRequestState::OPEN => domain::State::Open,
+                        $query::PullRequestState::MERGED => domain::State::Merged,
+                        _ => domain::State::Closed,
+                    };
+
+                    let closed_event_by = funky_flatten(pr.timeline_items.nodes)
+                        .into_iter()
+                        .filter_map(|event| match event {
+                            Event::ClosedEvent(closed) => closed.actor,
+                            _ => None,
+                        })
+                        .map(|a| domain::Author::new(a.login).with_link(a.url))
+                        .next_back();
+
+                    // Merged PRs have no closed event
+                    let closed_by = pr
+                        .merged_by
+                        .map(|a| domain::Author::new(a.login).with_link(a.url))
+                        .or(closed_event_by);
+
+                    domain::NewTrackedItem {
+                        state,
+                        foreign_id: pr.id,
+                        title: pr.title,
+                        link: pr.url,
+                        by: author!(pr.author, $query::PullRequestDetailsAuthorOn),
+                        labels,
+                        labels_truncated,
+                        timeline: vec![],
+                        details: domain::ItemDetails::PR {
+                            reviews: domain::Reviews::from(pr.review_status),
+                            checks: domain::Checks::from(pr.status_checks),
+                        },
+                        last_updated: pr.updated_at,
+                        number: pr.number as i32,
+                        closed_at: pr.closed_at,
+                        closed_by,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+pull_request_from_fragment!(broad_repo_view, nodes_view, pull_request_view);
+
+macro_rules! discussion_from_fragment {
+    ($($query:ident),+) => {
+        $(
+            impl From<$query::DiscussionDetails> for domain::NewTrackedItem {
+                fn from(discussion: $query::DiscussionDetails) -> Self {
+                    let (labels, labels_truncated) = labels!(discussion.labels);
+
+                    let state = if discussion.closed {
+                        domain::State::Closed
+                    } else {
+                        domain::State::Open
+                    };
+
+                    domain::NewTrackedItem {
+                        state,
+                        foreign_id: discussion.id,
+                        title: discussion.title,
+                        link: discussion.url,
+                        by: author!(discussion.author, $query::DiscussionDetailsAuthorOn),
+                        labels,
+                        labels_truncated,
+                        timeline: vec![],
+                        details: domain::ItemDetails::Discussion(domain::Discussion {
+                            category: discussion.category.name,
+                            // Categories that don't take answers leave this out
+                            answered: discussion.is_answered.unwrap_or(false),
+                            comments: discussion.comments.total_count as i32,
+                        }),
+                        last_updated: discussion.updated_at,
+                        number: discussion.number as i32,
+                        closed_at: discussion.closed_at,
+                        closed_by: None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+discussion_from_fragment!(broad_repo_view, discussion_view, nodes_view);
+
+/// Issues and PRs have their own timeline types, but share the fragments of the events we keep.
+macro_rules! timeline_events {
+    ($nodes:expr, $node:path) => {{
+        use $node as Node;
+
+        funky_flatten($nodes)
+            .into_iter()
+            .filter_map(|node| match node {
+                Node::IssueComment(comment) => Some(comment.comment_activity.into()),
+                Node::ClosedEvent(closed) => Some(closed.closed_activity.into()),
+                Node::CrossReferencedEvent(reference) => {
+                    Some(reference.cross_reference_activity.into())
+                }
+                _ => None,
+            })
+            .collect::<Vec<domain::TimelineEvent>>()
+    }};
+}
+
+/// Every query spreading the activity fragments gets its own copy of their types.
+macro_rules! activity_from_fragments {
+    ($($query:ident),+) => {
+        $(
+            impl From<$query::CommentActivity> for domain::TimelineEvent {
+                fn from(comment: $query::CommentActivity) -> Self {
+                    domain::TimelineEvent {
+                        kind: domain::TimelineEventKind::Commented,
+                        by: login_or_ghost(comment.author.map(|a| a.login)),
+                        at: comment.published_at.unwrap_or(comment.created_at),
+                        detail: Some(excerpt(&comment.body_text)),
+                    }
+                }
+            }
+
+            impl From<$query::ClosedActivity> for domain::TimelineEvent {
+                fn from(closed: $query::ClosedActivity) -> Self {
+                    domain::TimelineEvent {
+                        kind: domain::TimelineEventKind::Closed,
+                        by: login_or_ghost(closed.actor.map(|a| a.login)),
+                        at: closed.created_at,
+                        detail: None,
+                    }
+                }
+            }
+
+            impl From<$query::CrossReferenceActivity> for domain::TimelineEvent {
+                fn from(reference: $query::CrossReferenceActivity) -> Self {
+                    use $query::CrossReferenceActivitySource as Source;
+
+                    let source = match reference.source {
+                        Source::Issue(issue) => format!(
+                            "{}#{}: {}",
+                            issue.repository.name_with_owner, issue.number, issue.title
+                        ),
+                        Source::PullRequest(pr) => format!(
+                            "{}#{}: {}",
+                            pr.repository.name_with_owner, pr.number, pr.title
+                        ),
+                    };
+
+                    domain::TimelineEvent {
+                        kind: domain::TimelineEventKind::CrossReferenced,
+                        by: login_or_ghost(reference.actor.map(|a| a.login)),
+                        at: reference.created_at,
+                        detail: Some(source),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+activity_from_fragments!(issue_with_activity, nodes_view);
+
+fn login_or_ghost(login: Option<String>) -> String {
+    login.unwrap_or_else(|| "ghost".to_string())
+}
+
+/// The start of a comment, cut at `EXCERPT_LENGTH` characters, so that a card does not have to show all of it.
+fn excerpt(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(EXCERPT_LENGTH) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text.to_string(),
     }
 }
 
@@ -100,104 +893,383 @@ fn funky_flatten<T>(input: Option<Vec<Option<T>>>) -> Vec<T> {
         .collect::<Vec<T>>()
 }
 
+#[async_trait]
 impl domain::ClientForRepositories for GithubClient {
-    fn repo_exists(&self, repo: &domain::RepoName) -> Result<bool> {
+    fn rate_limit(&self) -> Option<domain::RateLimit> {
+        self.rate_limit.lock().unwrap().clone()
+    }
+
+    async fn repo_exists(&self, repo: &domain::RepoName) -> Result<Option<domain::RemoteRepo>> {
         let query = RepoExistsView::build_query(repo_exists_view::Variables {
             owner: repo.owner.clone(),
             name: repo.name.clone(),
         });
 
-        match self.make_request::<graphql_client::QueryBody<repo_exists_view::Variables>, repo_exists_view::ResponseData>(query) {
-            Ok(_) => Result::Ok(true),
-            Err(_) => Result::Ok(false),
+        match self.make_request::<graphql_client::QueryBody<repo_exists_view::Variables>, repo_exists_view::ResponseData>(query).await {
+            Ok(data) => data
+                .repository
+                .map(|found| {
+                    Ok(domain::RemoteRepo {
+                        foreign_id: found.id,
+                        name: domain::RepoName::from(found.name_with_owner)?,
+                    })
+                })
+                .transpose(),
+            Err(e) => match e.downcast_ref::<domain::ClientError>() {
+                Some(domain::ClientError::NotFound(_)) => Result::Ok(None),
+                _ => Result::Err(e),
+            },
+        }
+    }
+
+    async fn entire_repo(&self, repo: &domain::RepoName) -> Result<domain::LiveItems> {
+        let mut items = Vec::new();
+
+        let mut prs_after = None;
+        let mut issues_after = None;
+        let mut discussions_after = None;
+        let mut more_prs = true;
+        let mut more_issues = true;
+        let mut more_discussions = true;
+        let mut fetched = 0;
+
+        while (more_prs || more_issues || more_discussions) && fetched < self.max_items {
+            let page_size = std::cmp::min(PAGE_SIZE, self.max_items - fetched);
+
+            let query = BroadRepoView::build_query(broad_repo_view::Variables {
+                owner: repo.owner.clone(),
+                name: repo.name.clone(),
+                page_size: page_size as i64,
+                prs_after: prs_after.clone(),
+                issues_after: issues_after.clone(),
+                discussions_after: discussions_after.clone(),
+                labels: self.max_labels as i64,
+            });
+
+            let data: broad_repo_view::ResponseData = self.make_request(query).await?;
+            let broad_repo_view::BroadRepoViewRepository {
+                pull_requests,
+                issues,
+                discussions,
+            } = data.repository.possibly("repository not present")?;
+
+            // A connection that has run out of pages is still part of the query,
+            // so its (empty or repeated) nodes are skipped rather than appended.
+            if more_prs {
+                for pr in funky_flatten(pull_requests.nodes) {
+                    items.push(domain::NewTrackedItem::from(pr.pull_request_details));
+                }
+
+                more_prs = pull_requests.page_info.has_next_page;
+                prs_after = pull_requests.page_info.end_cursor;
+            }
+
+            if more_issues {
+                for issue in funky_flatten(issues.nodes) {
+                    items.push(domain::NewTrackedItem::from(issue.issue_details));
+                }
+
+                more_issues = issues.page_info.has_next_page;
+                issues_after = issues.page_info.end_cursor;
+            }
+
+            if more_discussions {
+                for discussion in funky_flatten(discussions.nodes) {
+                    items.push(domain::NewTrackedItem::from(discussion.discussion_details));
+                }
+
+                more_discussions = discussions.page_info.has_next_page;
+                discussions_after = discussions.page_info.end_cursor;
+            }
+
+            fetched += page_size;
+        }
+
+        Result::Ok(domain::LiveItems {
+            items,
+            truncated: more_prs || more_issues || more_discussions,
+        })
+    }
+
+    async fn refresh_items(&self, foreign_ids: &[String]) -> Result<domain::RefreshedItems> {
+        let mut items = Vec::new();
+        let mut failed = Vec::new();
+
+        for ids in foreign_ids.chunks(PAGE_SIZE) {
+            let query = NodesView::build_query(nodes_view::Variables {
+                ids: ids.to_vec(),
+                labels: self.max_labels as i64,
+                events: self.timeline_events as i64,
+            });
+
+            // One failed chunk should not keep the others from being refreshed
+            let data: nodes_view::ResponseData = match self.make_request(query).await {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!("could not refresh a chunk of {} items: {:#}", ids.len(), e);
+                    failed.extend_from_slice(ids);
+                    continue;
+                }
+            };
+
+            // Nodes that were deleted on GitHub come back as `null`
+            for node in funky_flatten(Some(data.nodes)) {
+                match node.on {
+                    nodes_view::NodesViewNodesOn::Issue(issue) => {
+                        items.push(domain::NewTrackedItem {
+                            timeline: timeline_events!(
+                                issue.activity.nodes,
+                                nodes_view::NodesViewNodesOnIssueActivityNodes
+                            ),
+                            ..domain::NewTrackedItem::from(issue.issue_details)
+                        })
+                    }
+                    nodes_view::NodesViewNodesOn::PullRequest(pr) => {
+                        items.push(domain::NewTrackedItem {
+                            timeline: timeline_events!(
+                                pr.activity.nodes,
+                                nodes_view::NodesViewNodesOnPullRequestActivityNodes
+                            ),
+                            ..domain::NewTrackedItem::from(pr.pull_request_details)
+                        })
+                    }
+                    nodes_view::NodesViewNodesOn::Discussion(discussion) => {
+                        items.push(domain::NewTrackedItem::from(discussion.discussion_details))
+                    }
+                    _ => log::warn!("ignoring a node that is not an issue, a PR or a discussion"),
+                }
+            }
+        }
+
+        Result::Ok(domain::RefreshedItems { items, failed })
+    }
+
+    async fn refresh_repos(&self, foreign_ids: &[String]) -> Result<Vec<domain::RemoteRepo>> {
+        let mut repos = Vec::new();
+
+        for ids in foreign_ids.chunks(PAGE_SIZE) {
+            let query =
+                RepoNodesView::build_query(repo_nodes_view::Variables { ids: ids.to_vec() });
+
+            let data: repo_nodes_view::ResponseData = match self.make_request(query).await {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!("could not look up a chunk of {} repos: {:#}", ids.len(), e);
+                    continue;
+                }
+            };
+
+            for node in funky_flatten(Some(data.nodes)) {
+                match node.on {
+                    repo_nodes_view::RepoNodesViewNodesOn::Repository(repo) => {
+                        match domain::RepoName::from(repo.name_with_owner) {
+                            Ok(name) => repos.push(domain::RemoteRepo {
+                                foreign_id: repo.id,
+                                name,
+                            }),
+                            Err(e) => log::warn!("ignoring a repo GitHub named oddly: {:#}", e),
+                        }
+                    }
+                    _ => log::warn!("ignoring a node that is not a repository"),
+                }
+            }
         }
+
+        Result::Ok(repos)
     }
 
-    fn entire_repo(&self, repo: &domain::RepoName) -> Result<Vec<domain::NewTrackedItem>> {
-        let query = BroadRepoView::build_query(broad_repo_view::Variables {
+    async fn discussion(&self, repo: &domain::RepoName, nr: i32) -> Result<domain::NewTrackedItem> {
+        let query = DiscussionView::build_query(discussion_view::Variables {
             owner: repo.owner.clone(),
             name: repo.name.clone(),
+            nr: nr as i64,
+            labels: self.max_labels as i64,
         });
 
-        let data: broad_repo_view::ResponseData = self.make_request(query)?;
-        let broad_repo_view::BroadRepoViewRepository {
-            pull_requests,
-            issues,
-        } = data.repository.possibly("repository not present")?;
+        let data: discussion_view::ResponseData = self.make_request(query).await?;
 
-        let mut items = Vec::new();
-        for maybe_pr in pull_requests.nodes.possibly("nodes not present")? {
-            let pr = maybe_pr.possibly("no pr present")?;
-            let labels = funky_flatten(pr.labels.possibly("no lables")?.nodes)
-                .into_iter()
-                .map(|s| domain::Label::new(s.name))
-                .collect();
-
-            let author = pr
-                .author
-                .map(|a| domain::Author::new(a.login).with_link(a.url))
-                .unwrap_or(domain::Author::new("ghost").with_link("https://github.com/ghost"));
-
-            let state = match pr.state {
-                broad_repo_view::PullRequestState::OPEN => domain::State::Open,
-                _ => domain::State::Closed,
+        let discussion = data
+            .repository
+            .possibly("no repository")?
+            .discussion
+            .possibly("no discussion")?;
+
+        Result::Ok(domain::NewTrackedItem::from(discussion.discussion_details))
+    }
+
+    async fn releases(
+        &self,
+        foreign_ids: &[String],
+    ) -> Result<HashMap<String, Vec<domain::NewTrackedItem>>> {
+        use releases_view::ReleasesViewNodesOn as Node;
+
+        let mut releases = HashMap::new();
+
+        for ids in foreign_ids.chunks(PAGE_SIZE) {
+            let query = ReleasesView::build_query(releases_view::Variables {
+                ids: ids.to_vec(),
+                count: self.max_releases as i64,
+            });
+
+            let data: releases_view::ResponseData = match self.make_request(query).await {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!(
+                        "could not fetch the releases of {} repos: {:#}",
+                        ids.len(),
+                        e
+                    );
+                    continue;
+                }
             };
 
-            items.push(domain::NewTrackedItem {
-                state,
-                foreign_id: pr.id,
-                title: pr.title,
-                link: pr.url,
-                by: author,
-                labels,
-                kind: domain::ItemKind::PR,
-                last_updated: pr.updated_at,
-                number: pr.number as i32,
-            })
+            for node in funky_flatten(Some(data.nodes)) {
+                let repo = match node.on {
+                    Node::Repository(repo) => repo,
+                    _ => {
+                        log::warn!("ignoring a node that is not a repository");
+                        continue;
+                    }
+                };
+
+                let latest = funky_flatten(repo.releases.nodes)
+                    .into_iter()
+                    // Drafts are only visible to maintainers and may never be published
+                    .filter(|release| !release.is_draft)
+                    .filter_map(|release| {
+                        let published_at = release.published_at?;
+                        // The number is what the history of a release is kept under
+                        let number = match release.database_id.map(i32::try_from) {
+                            Some(Ok(number)) => number,
+                            _ => {
+                                log::warn!(
+                                    "skipping release {} without a usable ID",
+                                    release.tag_name
+                                );
+                                return None;
+                            }
+                        };
+                        let tag = release.tag_name;
+                        let by = match release.author {
+                            Some(author) => domain::Author::new(author.login)
+                                .with_link(author.url)
+                                .with_avatar(author.avatar_url),
+                            None => domain::Author::new("ghost"),
+                        };
+
+                        Some(domain::NewTrackedItem {
+                            title: release.name.unwrap_or_else(|| tag.clone()),
+                            link: release.url,
+                            by,
+                            labels: vec![],
+                            labels_truncated: false,
+                            timeline: vec![],
+                            details: domain::ItemDetails::Release(domain::Release {
+                                tag,
+                                prerelease: release.is_prerelease,
+                                published_at,
+                            }),
+                            state: domain::State::Open,
+                            foreign_id: release.id,
+                            number,
+                            last_updated: release.updated_at,
+                            closed_at: None,
+                            closed_by: None,
+                        })
+                    })
+                    .collect();
+
+                releases.insert(repo.id, latest);
+            }
         }
 
-        for maybe_issue in issues.nodes.possibly("nodes not present")? {
-            let issue = maybe_issue.possibly("no issue present")?;
-            let labels = funky_flatten(issue.labels.possibly("no lables")?.nodes)
-                .into_iter()
-                .map(|s| domain::Label::new(s.name))
-                .collect();
-
-            let author = issue
-                .author
-                .map(|a| domain::Author::new(a.login).with_link(a.url))
-                .unwrap_or(domain::Author::new("ghost").with_link("https://github.com/ghost"));
-
-            let state = match issue.state {
-                broad_repo_view::IssueState::OPEN => domain::State::Open,
-                _ => domain::State::Closed,
+        Result::Ok(releases)
+    }
+
+    async fn last_commits(
+        &self,
+        foreign_ids: &[String],
+    ) -> Result<HashMap<String, domain::Commit>> {
+        use last_commits_view::LastCommitsViewNodesOn as Node;
+        use last_commits_view::LastCommitsViewNodesOnRepositoryDefaultBranchRefTargetOn as Target;
+
+        let mut commits = HashMap::new();
+
+        for ids in foreign_ids.chunks(PAGE_SIZE) {
+            let query =
+                LastCommitsView::build_query(last_commits_view::Variables { ids: ids.to_vec() });
+
+            let data: last_commits_view::ResponseData = match self.make_request(query).await {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!(
+                        "could not fetch the last commits of {} repos: {:#}",
+                        ids.len(),
+                        e
+                    );
+                    continue;
+                }
             };
 
-            items.push(domain::NewTrackedItem {
-                state,
-                foreign_id: issue.id,
-                title: issue.title,
-                link: issue.url,
-                by: author,
-                labels,
-                kind: domain::ItemKind::Issue,
-                last_updated: issue.updated_at,
-                number: issue.number as i32,
-            })
+            for node in funky_flatten(Some(data.nodes)) {
+                let repo = match node.on {
+                    Node::Repository(repo) => repo,
+                    _ => {
+                        log::warn!("ignoring a node that is not a repository");
+                        continue;
+                    }
+                };
+
+                // Empty repos have no default branch yet
+                let branch = match repo.default_branch_ref {
+                    Some(branch) => branch,
+                    None => continue,
+                };
+
+                let commit = match branch.target.on {
+                    Target::Commit(commit) => commit,
+                    _ => continue,
+                };
+
+                let author = commit
+                    .author
+                    .and_then(|a| a.user.map(|u| u.login).or(a.name))
+                    .unwrap_or_else(|| "ghost".to_string());
+
+                let ci_status = commit.status_check_rollup.map(|rollup| match rollup.state {
+                    last_commits_view::StatusState::SUCCESS => domain::CiStatus::Passing,
+                    last_commits_view::StatusState::FAILURE
+                    | last_commits_view::StatusState::ERROR => domain::CiStatus::Failing,
+                    _ => domain::CiStatus::Pending,
+                });
+
+                commits.insert(
+                    repo.id,
+                    domain::Commit {
+                        branch: branch.name,
+                        sha: commit.oid,
+                        headline: commit.message_headline,
+                        author,
+                        committed_at: commit.committed_date,
+                        ci_status,
+                    },
+                );
+            }
         }
 
-        Result::Ok(items)
+        Result::Ok(commits)
     }
 
     /// This will be used in the update-phase
-    fn issue(&self, repo: &domain::RepoName, nr: i32) -> Result<domain::NewTrackedItem> {
+    async fn issue(&self, repo: &domain::RepoName, nr: i32) -> Result<domain::NewTrackedItem> {
         let query = IssueView::build_query(issue_view::Variables {
             owner: repo.owner.clone(),
             name: repo.name.clone(),
             nr: nr as i64,
+            labels: self.max_labels as i64,
         });
 
-        let data: issue_view::ResponseData = self.make_request(query)?;
+        let data: issue_view::ResponseData = self.make_request(query).await?;
 
         let issue = data
             .repository
@@ -205,43 +1277,23 @@ impl domain::ClientForRepositories for GithubClient {
             .issue
             .possibly("no issue")?;
 
-        let labels = funky_flatten(issue.labels.possibly("no lables")?.nodes)
-            .into_iter()
-            .map(|s| domain::Label::new(s.name))
-            .collect();
-
-        let author = issue
-            .author
-            .map(|a| domain::Author::new(a.login).with_link(a.url))
-            .unwrap_or(domain::Author::new("ghost").with_link("https://github.com/ghost"));
-
-        let state = match issue.state {
-            issue_view::IssueState::OPEN => domain::State::Open,
-            _ => domain::State::Closed,
-        };
-
-        Result::Ok(domain::NewTrackedItem {
-            state,
-            foreign_id: issue.id,
-            title: issue.title,
-            link: issue.url,
-            by: author,
-            labels,
-            kind: domain::ItemKind::Issue,
-            last_updated: issue.updated_at,
-            number: issue.number as i32,
-        })
+        Result::Ok(domain::NewTrackedItem::from(issue.issue_details))
     }
 
     /// This will be used in the update-phase
-    fn pull_request(&self, repo: &domain::RepoName, nr: i32) -> Result<domain::NewTrackedItem> {
+    async fn pull_request(
+        &self,
+        repo: &domain::RepoName,
+        nr: i32,
+    ) -> Result<domain::NewTrackedItem> {
         let query = PullRequestView::build_query(pull_request_view::Variables {
             owner: repo.owner.clone(),
             name: repo.name.clone(),
             nr: nr as i64,
+            labels: self.max_labels as i64,
         });
 
-        let data: pull_request_view::ResponseData = self.make_request(query)?;
+        let data: pull_request_view::ResponseData = self.make_request(query).await?;
 
         let pr = data
             .repository
@@ -249,37 +1301,53 @@ impl domain::ClientForRepositories for GithubClient {
             .pull_request
             .possibly("no pull request")?;
 
-        let labels = funky_flatten(pr.labels.possibly("no labels")?.nodes)
-            .into_iter()
-            .map(|s| domain::Label::new(s.name))
-            .collect();
+        Result::Ok(domain::NewTrackedItem::from(pr.pull_request_details))
+    }
 
-        let author = pr
-            .author
-            .map(|a| domain::Author::new(a.login).with_link(a.url))
-            .unwrap_or(domain::Author::new("ghost").with_link("https://github.com/ghost"));
+    async fn timeline(
+        &self,
+        repo: &domain::RepoName,
+        nr: i32,
+    ) -> Result<Vec<domain::TimelineEvent>> {
+        use issue_with_activity::IssueWithActivityRepositoryIssueOrPullRequest as Item;
+
+        let query = IssueWithActivity::build_query(issue_with_activity::Variables {
+            owner: repo.owner.clone(),
+            name: repo.name.clone(),
+            nr: nr as i64,
+            events: self.timeline_events as i64,
+        });
+
+        let data: issue_with_activity::ResponseData = self.make_request(query).await?;
+
+        let item = data
+            .repository
+            .possibly("no repository")?
+            .issue_or_pull_request
+            .possibly("no issue or pull request")?;
 
-        let state = match pr.state {
-            pull_request_view::PullRequestState::OPEN => domain::State::Open,
-            _ => domain::State::Closed,
+        let events = match item {
+            Item::Issue(issue) => timeline_events!(
+                issue.timeline_items.nodes,
+                issue_with_activity::IssueWithActivityRepositoryIssueOrPullRequestOnIssueTimelineItemsNodes
+            ),
+            Item::PullRequest(pr) => timeline_events!(
+                pr.timeline_items.nodes,
+                issue_with_activity::IssueWithActivityRepositoryIssueOrPullRequestOnPullRequestTimelineItemsNodes
+            ),
         };
 
-        Result::Ok(domain::NewTrackedItem {
-            state,
-            foreign_id: pr.id,
-            title: pr.title,
-            link: pr.url,
-            by: author,
-            labels,
-            kind: domain::ItemKind::PR,
-            last_updated: pr.updated_at,
-            number: pr.number as i32,
-        })
+        Result::Ok(events)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+
     use super::*;
     use crate::domain::{ClientForRepositories, RepoName};
 
@@ -298,9 +1366,8 @@ mod tests {
         let client = GithubClient::new(token);
         let repo = domain::RepoName::from("felipesere/advisorex").unwrap();
 
-        let pr = client
-            .pull_request(&repo, 101)
-            .expect("should be able to get PRs");
+        let pr =
+            task::block_on(client.pull_request(&repo, 101)).expect("should be able to get PRs");
 
         assert_eq!(pr.title, "Advice notes".to_string());
     }
@@ -316,9 +1383,7 @@ mod tests {
 
         let repo = domain::RepoName::from("felipesere/advisorex").unwrap();
 
-        let issue = client
-            .issue(&repo, 117)
-            .expect("should be able to get issues");
+        let issue = task::block_on(client.issue(&repo, 117)).expect("should be able to get issues");
 
         assert_eq!(issue.title, "Try out Github Actions".to_string());
     }
@@ -332,13 +1397,13 @@ mod tests {
         let token = maybe_token.unwrap();
         let client = GithubClient::new(token);
 
-        let good_repo = RepoName::from("felipesere/advisorex").unwrap();
-        let exists = client.repo_exists(&good_repo).unwrap();
-        assert!(exists);
+        let good_repo = RepoName::from("FelipeSere/AdvisorEx").unwrap();
+        let exists = task::block_on(client.repo_exists(&good_repo)).unwrap();
+        assert_eq!(exists.unwrap().name.to_string(), "felipesere/advisorex");
 
         let bad_repo = RepoName::from("felipesere/foo").unwrap();
-        let not_exists = client.repo_exists(&bad_repo).unwrap();
-        assert!(!not_exists);
+        let not_exists = task::block_on(client.repo_exists(&bad_repo)).unwrap();
+        assert!(not_exists.is_none());
     }
 
     #[test]
@@ -352,10 +1417,1069 @@ mod tests {
 
         let repo = domain::RepoName::from("felipesere/advisorex").unwrap();
 
-        let entire_repo = client
-            .entire_repo(&repo)
-            .expect("should be able to get PRs");
+        let entire_repo =
+            task::block_on(client.entire_repo(&repo)).expect("should be able to get PRs");
+
+        assert_eq!(entire_repo.items.len(), 11);
+        assert!(!entire_repo.truncated);
+    }
+
+    /// Stands in for the GraphQL API, answering with canned responses in order.
+    struct FakeGithub {
+        responses: Mutex<VecDeque<tide::Response>>,
+        requests: Mutex<Vec<ReceivedRequest>>,
+    }
+
+    struct ReceivedRequest {
+        headers: HashMap<String, String>,
+        body: Value,
+    }
+
+    impl FakeGithub {
+        fn requests(&self) -> std::sync::MutexGuard<'_, Vec<ReceivedRequest>> {
+            self.requests.lock().unwrap()
+        }
+    }
+
+    /// Serves the responses on a free local port and returns the API base to point a client at.
+    fn fake_github(responses: Vec<tide::Response>) -> (String, Arc<FakeGithub>) {
+        let fake = Arc::new(FakeGithub {
+            responses: Mutex::new(responses.into_iter().collect()),
+            requests: Mutex::new(Vec::new()),
+        });
+
+        let mut app = tide::with_state(fake.clone());
+        app.at("/graphql")
+            .post(|mut req: tide::Request<Arc<FakeGithub>>| async move {
+                let body: Value = req.body_json().await.unwrap();
+                let headers = req
+                    .headers()
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_str().unwrap().to_string()))
+                    .collect();
+
+                let fake = req.state();
+                fake.requests().push(ReceivedRequest { headers, body });
+
+                let next = fake.responses.lock().unwrap().pop_front();
+                next.unwrap_or_else(|| tide::Response::new(500))
+            });
+
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .unwrap();
+        task::spawn(app.listen(address));
+
+        while std::net::TcpStream::connect(address).is_err() {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        (format!("http://{}", address), fake)
+    }
+
+    fn reply(status: u16, json: Value) -> tide::Response {
+        tide::Response::new(status).body_json(&json).unwrap()
+    }
+
+    fn issue_node(nr: i64) -> Value {
+        json!({
+            "id": format!("issue-{}", nr),
+            "state": "OPEN",
+            "updatedAt": "2020-02-01T10:00:00Z",
+            "title": format!("Issue {}", nr),
+            "number": nr,
+            "author": {
+                "__typename": "User",
+                "login": "someone",
+                "url": "https://github.com/someone",
+                "avatarUrl": "https://avatars.example.com/someone"
+            },
+            "labels": { "totalCount": 0, "nodes": [] },
+            "url": format!("https://github.com/foo/bar/issues/{}", nr),
+            "closedAt": null,
+            "timelineItems": { "nodes": [] },
+            "activity": { "nodes": [] },
+        })
+    }
+
+    fn discussion_node(nr: i64, answered: Option<bool>) -> Value {
+        json!({
+            "id": format!("discussion-{}", nr),
+            "number": nr,
+            "title": format!("Discussion {}", nr),
+            "url": format!("https://github.com/foo/bar/discussions/{}", nr),
+            "closed": false,
+            "closedAt": null,
+            "updatedAt": "2020-02-01T10:00:00Z",
+            "isAnswered": answered,
+            "category": { "name": "Q&A" },
+            "comments": { "totalCount": 4 },
+            "author": {
+                "__typename": "User",
+                "login": "someone",
+                "url": "https://github.com/someone",
+                "avatarUrl": "https://avatars.example.com/someone"
+            },
+            "labels": { "totalCount": 0, "nodes": [] },
+        })
+    }
+
+    /// What GitHub adds to `errors` for a node of a `nodes(ids:)` query that is gone.
+    fn missing_node(index: usize, id: &str) -> Value {
+        json!({
+            "type": "NOT_FOUND",
+            "path": ["nodes", index],
+            "locations": [{ "line": 2, "column": 3 }],
+            "message": format!("Could not resolve to a node with the global id of '{}'", id)
+        })
+    }
+
+    fn broad_repo_page(issues: Vec<Value>, end_cursor: &str, has_next_page: bool) -> Value {
+        json!({
+            "data": {
+                "repository": {
+                    "pullRequests": {
+                        "totalCount": 0,
+                        "pageInfo": { "endCursor": null, "hasNextPage": false },
+                        "nodes": []
+                    },
+                    "issues": {
+                        "totalCount": 3,
+                        "pageInfo": { "endCursor": end_cursor, "hasNextPage": has_next_page },
+                        "nodes": issues
+                    },
+                    "discussions": {
+                        "pageInfo": { "endCursor": null, "hasNextPage": false },
+                        "nodes": []
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn talks_to_a_configured_api_with_extra_headers() {
+        let (api_url, fake) = fake_github(vec![reply(
+            200,
+            json!({ "data": { "repository": { "id": "MDEwOlJlcG9zaXRvcnkx", "nameWithOwner": "foo/bar" } } }),
+        )]);
+
+        let client = GithubClient::new("some-token")
+            .with_api_url(api_url)
+            .with_header("X-Enterprise-Thing", "some-value")
+            .unwrap();
+
+        let repo = RepoName::from("foo/bar").unwrap();
+        assert!(task::block_on(client.repo_exists(&repo)).unwrap().is_some());
+
+        let requests = fake.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].headers["authorization"], "Bearer some-token");
+        assert_eq!(requests[0].headers["x-enterprise-thing"], "some-value");
+    }
+
+    #[test]
+    fn refuses_headers_that_are_not_valid_http() {
+        assert!(GithubClient::new("some-token")
+            .with_header("Not A Name", "some-value")
+            .is_err());
+        assert!(GithubClient::new("some-token")
+            .with_header("X-Enterprise-Thing", "line\nbreak")
+            .is_err());
+    }
+
+    #[test]
+    fn pages_through_the_broad_repo_view() {
+        let (api_url, fake) = fake_github(vec![
+            reply(
+                200,
+                broad_repo_page(vec![issue_node(1), issue_node(2)], "cursor-2", true),
+            ),
+            reply(200, broad_repo_page(vec![issue_node(3)], "cursor-3", false)),
+        ]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        let live = task::block_on(client.entire_repo(&repo)).unwrap();
+
+        assert_eq!(live.items.len(), 3);
+        assert!(!live.truncated);
+
+        let requests = fake.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body["variables"]["issues_after"], "cursor-2");
+    }
+
+    #[test]
+    fn reports_a_truncated_broad_repo_view() {
+        let (api_url, fake) = fake_github(vec![reply(
+            200,
+            broad_repo_page(vec![issue_node(1), issue_node(2)], "cursor-2", true),
+        )]);
+
+        let client = GithubClient::new("some-token")
+            .with_api_url(api_url)
+            .with_max_items(2);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        let live = task::block_on(client.entire_repo(&repo)).unwrap();
+
+        assert_eq!(live.items.len(), 2);
+        assert!(live.truncated);
+        assert_eq!(fake.requests()[0].body["variables"]["page_size"], 2);
+        // Capping should leave out the stalest items, not the most recently active ones
+        let query = fake.requests()[0].body["query"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(!query.contains("direction: ASC"));
+        assert_eq!(query.matches("direction: DESC").count(), 3);
+    }
+
+    #[test]
+    fn lists_open_discussions_in_the_broad_repo_view() {
+        let mut page = broad_repo_page(vec![issue_node(1)], "cursor-1", false);
+        page["data"]["repository"]["discussions"]["nodes"] = json!([discussion_node(2, None)]);
+        let (api_url, _fake) = fake_github(vec![reply(200, page)]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        let live = task::block_on(client.entire_repo(&repo)).unwrap();
+
+        assert_eq!(live.items.len(), 2);
+        let discussion = &live.items[1];
+        assert_eq!(discussion.kind(), domain::ItemKind::Discussion);
+        assert_eq!(discussion.number, 2);
+        assert_eq!(
+            discussion.details,
+            domain::ItemDetails::Discussion(domain::Discussion {
+                category: "Q&A".into(),
+                answered: false,
+                comments: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn refreshes_discussions_along_with_issues_and_prs() {
+        let mut node = discussion_node(2, Some(true));
+        node["__typename"] = json!("Discussion");
+        let (api_url, _fake) = fake_github(vec![reply(
+            200,
+            json!({ "data": { "nodes": [node, null] } }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+
+        let refreshed =
+            task::block_on(client.refresh_items(&["discussion-2".into(), "gone".into()]))
+                .unwrap()
+                .items;
+
+        assert_eq!(refreshed.len(), 1);
+        assert_eq!(refreshed[0].foreign_id, "discussion-2");
+        assert!(refreshed[0].details.discussion().unwrap().answered);
+    }
+
+    #[test]
+    fn a_repo_that_is_not_found_does_not_exist() {
+        let (api_url, _fake) = fake_github(vec![reply(
+            200,
+            json!({
+                "data": { "repository": null },
+                "errors": [{ "type": "NOT_FOUND", "message": "Could not resolve to a Repository" }]
+            }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        assert!(task::block_on(client.repo_exists(&repo)).unwrap().is_none());
+    }
+
+    #[test]
+    fn failing_to_check_a_repo_is_not_mistaken_for_it_missing() {
+        let (api_url, _fake) =
+            fake_github(vec![reply(401, json!({ "message": "Bad credentials" }))]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        assert!(task::block_on(client.repo_exists(&repo)).is_err());
+    }
+
+    #[test]
+    fn keeps_refreshing_after_a_chunk_fails() {
+        let mut node = issue_node(101);
+        node["__typename"] = json!("Issue");
+        let (api_url, fake) = fake_github(vec![
+            reply(400, json!({ "message": "Bad Request" })),
+            reply(200, json!({ "data": { "nodes": [node] } })),
+        ]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let ids = (1..=101)
+            .map(|nr| format!("issue-{}", nr))
+            .collect::<Vec<_>>();
+
+        let refreshed = task::block_on(client.refresh_items(&ids)).unwrap();
+
+        assert_eq!(fake.requests().len(), 2);
+        assert_eq!(refreshed.items.len(), 1);
+        assert_eq!(refreshed.items[0].foreign_id, "issue-101");
+        assert_eq!(refreshed.failed, ids[..100].to_vec());
+    }
+
+    #[test]
+    fn refreshes_the_other_items_when_one_is_not_found() {
+        let mut first = issue_node(1);
+        first["__typename"] = json!("Issue");
+        let mut third = issue_node(3);
+        third["__typename"] = json!("Issue");
+        let (api_url, _fake) = fake_github(vec![reply(
+            200,
+            json!({
+                "data": { "nodes": [first, null, third] },
+                "errors": [missing_node(1, "issue-2")]
+            }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let ids = vec![
+            "issue-1".to_string(),
+            "issue-2".to_string(),
+            "issue-3".to_string(),
+        ];
+
+        let refreshed = task::block_on(client.refresh_items(&ids)).unwrap().items;
+
+        let ids = refreshed
+            .iter()
+            .map(|i| i.foreign_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["issue-1", "issue-3"]);
+    }
+
+    #[test]
+    fn uses_the_data_when_the_list_of_errors_is_empty() {
+        let mut node = issue_node(1);
+        node["__typename"] = json!("Issue");
+        let (api_url, _fake) = fake_github(vec![reply(
+            200,
+            json!({ "data": { "nodes": [node] }, "errors": [] }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+
+        let refreshed = task::block_on(client.refresh_items(&["issue-1".to_string()]))
+            .unwrap()
+            .items;
+
+        assert_eq!(refreshed.len(), 1);
+    }
+
+    #[test]
+    fn remembers_the_rate_limit_of_the_last_query() {
+        let (api_url, _) = fake_github(vec![reply(
+            200,
+            json!({
+                "data": {
+                    "repository": { "id": "MDEwOlJlcG9zaXRvcnkx", "nameWithOwner": "foo/bar" },
+                    "rateLimit": {
+                        "limit": 5000,
+                        "cost": 1,
+                        "remaining": 4321,
+                        "resetAt": "2020-02-01T11:00:00Z"
+                    }
+                }
+            }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        assert!(client.rate_limit().is_none());
+
+        let repo = RepoName::from("foo/bar").unwrap();
+        task::block_on(client.repo_exists(&repo)).unwrap();
+
+        let rate_limit = client.rate_limit().unwrap();
+        assert_eq!(rate_limit.remaining, 4321);
+        assert_eq!(
+            rate_limit.reset_at.to_rfc3339(),
+            "2020-02-01T11:00:00+00:00"
+        );
+    }
+
+    fn impatient_client(api_url: String) -> GithubClient {
+        GithubClient::new("some-token")
+            .with_api_url(api_url)
+            .with_backoff(Backoff {
+                max_retries: 2,
+                base_delay: std::time::Duration::from_millis(1),
+                max_delay: std::time::Duration::from_millis(10),
+                ..Backoff::default()
+            })
+    }
+
+    fn issue_response(nr: i64) -> Value {
+        json!({ "data": { "repository": { "issue": issue_node(nr) } } })
+    }
+
+    #[test]
+    fn keeps_the_delay_within_bounds_after_many_attempts() {
+        let backoff = Backoff::default();
+
+        for attempt in [10, 63, 64, 1000, u32::MAX].iter() {
+            assert!(backoff.delay(*attempt) <= backoff.max_delay);
+        }
+    }
+
+    #[test]
+    fn retries_when_github_has_a_hiccup() {
+        let (api_url, fake) = fake_github(vec![
+            reply(502, json!({ "message": "Server Error" })),
+            reply(503, json!({ "message": "Server Error" })),
+            reply(200, issue_response(7)),
+        ]);
+
+        let client = impatient_client(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        let issue = task::block_on(client.issue(&repo, 7)).unwrap();
+
+        assert_eq!(issue.title, "Issue 7");
+        assert_eq!(fake.requests().len(), 3);
+    }
+
+    #[test]
+    fn retries_after_being_told_to_back_off() {
+        let (api_url, fake) = fake_github(vec![
+            reply(
+                403,
+                json!({ "message": "You have triggered an abuse detection mechanism." }),
+            )
+            .set_header("Retry-After", "0"),
+            reply(200, issue_response(7)),
+        ]);
+
+        let client = impatient_client(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        assert!(task::block_on(client.issue(&repo, 7)).is_ok());
+        assert_eq!(fake.requests().len(), 2);
+    }
+
+    #[test]
+    fn does_not_ask_again_before_github_allows_it() {
+        // GitHub's secondary rate limits tend to ask for a minute
+        let (api_url, fake) = fake_github(vec![
+            reply(
+                403,
+                json!({ "message": "You have exceeded a secondary rate limit." }),
+            )
+            .set_header("Retry-After", "60"),
+            reply(200, issue_response(7)),
+        ]);
+
+        let client = impatient_client(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        let error = task::block_on(client.issue(&repo, 7)).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<domain::ClientError>(),
+            Some(domain::ClientError::Transient(_))
+        ));
+        assert_eq!(fake.requests().len(), 1);
+    }
+
+    fn rate_limited(reset_at: chrono::DateTime<chrono::Utc>) -> Value {
+        json!({
+            "data": {
+                "repository": null,
+                "rateLimit": {
+                    "limit": 5000,
+                    "cost": 1,
+                    "remaining": 0,
+                    "resetAt": reset_at.to_rfc3339()
+                }
+            },
+            "errors": [{ "type": "RATE_LIMITED", "message": "API rate limit exceeded" }]
+        })
+    }
+
+    #[test]
+    fn retries_once_the_rate_limit_resets() {
+        let (api_url, fake) = fake_github(vec![
+            reply(200, rate_limited(chrono::Utc::now())),
+            reply(200, issue_response(7)),
+        ]);
+
+        let client = impatient_client(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        assert!(task::block_on(client.issue(&repo, 7)).is_ok());
+        assert_eq!(fake.requests().len(), 2);
+    }
+
+    #[test]
+    fn does_not_retry_before_the_rate_limit_resets() {
+        let reset_at = chrono::Utc::now() + chrono::Duration::hours(1);
+        let (api_url, fake) = fake_github(vec![
+            reply(200, rate_limited(reset_at)),
+            reply(200, issue_response(7)),
+        ]);
+
+        let client = impatient_client(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        let error = task::block_on(client.issue(&repo, 7)).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<domain::ClientError>(),
+            Some(domain::ClientError::Transient(_))
+        ));
+        assert_eq!(fake.requests().len(), 1);
+    }
+
+    #[test]
+    fn retries_a_response_that_is_not_json() {
+        let (api_url, fake) = fake_github(vec![
+            tide::Response::new(200).body_string("{\"data\": {\"repos".to_string()),
+            reply(200, issue_response(7)),
+        ]);
+
+        let client = impatient_client(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        assert!(task::block_on(client.issue(&repo, 7)).is_ok());
+        assert_eq!(fake.requests().len(), 2);
+    }
+
+    #[test]
+    fn understands_both_forms_of_retry_after() {
+        use chrono::TimeZone;
+        let now = chrono::Utc.ymd(2015, 10, 21).and_hms(7, 27, 0);
+
+        assert_eq!(
+            retry_after("60", now),
+            Some(std::time::Duration::from_secs(60))
+        );
+        assert_eq!(
+            retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(std::time::Duration::from_secs(60))
+        );
+        assert_eq!(
+            retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(std::time::Duration::from_secs(0))
+        );
+        assert_eq!(retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn gives_up_on_transient_failures_eventually() {
+        let (api_url, fake) = fake_github(vec![
+            reply(502, json!({})),
+            reply(502, json!({})),
+            reply(502, json!({})),
+        ]);
+
+        let client = impatient_client(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        let error = task::block_on(client.issue(&repo, 7)).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<domain::ClientError>(),
+            Some(domain::ClientError::Transient(_))
+        ));
+        assert_eq!(fake.requests().len(), 3);
+    }
+
+    #[test]
+    fn does_not_retry_permanent_failures() {
+        let (api_url, fake) =
+            fake_github(vec![reply(401, json!({ "message": "Bad credentials" }))]);
+
+        let client = impatient_client(api_url);
+        let repo = RepoName::from("foo/bar").unwrap();
+
+        let error = task::block_on(client.issue(&repo, 7)).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<domain::ClientError>(),
+            Some(domain::ClientError::Permanent(_))
+        ));
+        assert_eq!(fake.requests().len(), 1);
+    }
+
+    #[test]
+    fn refreshes_issues_and_prs_by_their_node_ids() {
+        let mut pr = issue_node(2);
+        pr["__typename"] = json!("PullRequest");
+        pr["state"] = json!("MERGED");
+        pr["isDraft"] = json!(false);
+        pr["closedAt"] = json!("2020-02-02T10:00:00Z");
+        pr["mergedBy"] =
+            json!({ "__typename": "User", "login": "merger", "url": "https://github.com/merger" });
+        pr["labels"] = json!({ "totalCount": 30, "nodes": [
+            { "name": "dependencies", "color": "0366d6", "description": null }
+        ] });
+        pr["reviewDecision"] = json!("APPROVED");
+        pr["reviewRequests"] = json!({ "nodes": [
+            { "requestedReviewer": { "__typename": "Team", "name": "reviewers" } },
+            { "requestedReviewer": { "__typename": "User", "login": "octocat" } }
+        ] });
+        pr["commits"] = json!({ "nodes": [{ "commit": { "statusCheckRollup": {
+            "state": "FAILURE",
+            "contexts": { "nodes": [
+                { "__typename": "CheckRun", "name": "build", "conclusion": "FAILURE" },
+                { "__typename": "CheckRun", "name": "lint", "conclusion": "SUCCESS" },
+                { "__typename": "StatusContext", "context": "ci/deploy", "state": "ERROR" }
+            ] }
+        } } }] });
+        pr["latestReviews"] = json!({ "nodes": [{
+            "state": "APPROVED",
+            "submittedAt": "2020-02-01T10:00:00Z",
+            "author": { "__typename": "User", "login": "hubot" }
+        }] });
+        pr["author"] = json!({
+            "__typename": "Bot",
+            "login": "dependabot",
+            "url": "https://github.com/apps/dependabot",
+            "avatarUrl": "https://avatars.example.com/dependabot"
+        });
+
+        let mut draft = issue_node(3);
+        draft["__typename"] = json!("PullRequest");
+        draft["isDraft"] = json!(true);
+        draft["mergedBy"] = json!(null);
+        draft["commits"] = json!({ "nodes": [] });
+
+        let mut issue = issue_node(1);
+        issue["__typename"] = json!("Issue");
+        issue["state"] = json!("CLOSED");
+        issue["closedAt"] = json!("2020-02-03T10:00:00Z");
+        issue["timelineItems"] = json!({ "nodes": [{
+            "__typename": "ClosedEvent",
+            "actor": { "__typename": "User", "login": "closer", "url": "https://github.com/closer" }
+        }] });
+        issue["activity"] = json!({ "nodes": [{
+            "__typename": "IssueComment",
+            "author": { "__typename": "User", "login": "hubot" },
+            "bodyText": "Fixed in the latest release.",
+            "publishedAt": "2020-02-03T09:00:00Z",
+            "createdAt": "2020-02-03T09:00:00Z"
+        }] });
+
+        let (api_url, fake) = fake_github(vec![reply(
+            200,
+            json!({
+                "data": { "nodes": [issue, null, pr, draft] },
+                "errors": [missing_node(1, "deleted")]
+            }),
+        )]);
+
+        let client = GithubClient::new("some-token")
+            .with_api_url(api_url)
+            .with_max_labels(1);
+
+        let ids = vec![
+            "issue-1".to_string(),
+            "deleted".to_string(),
+            "issue-2".to_string(),
+            "issue-3".to_string(),
+        ];
+        let items = task::block_on(client.refresh_items(&ids)).unwrap().items;
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].kind(), domain::ItemKind::Issue);
+        assert_eq!(items[0].state, domain::State::Closed);
+        assert_eq!(items[0].closed_by.as_ref().unwrap().name, "closer");
+        assert_eq!(items[0].timeline.len(), 1);
+        assert_eq!(
+            items[0].timeline[0].detail.as_deref(),
+            Some("Fixed in the latest release.")
+        );
+        assert!(items[1].timeline.is_empty());
+        assert_eq!(items[0].by.kind, domain::AuthorKind::User);
+        assert_eq!(
+            items[0].by.avatar.as_deref(),
+            Some("https://avatars.example.com/someone")
+        );
+        assert_eq!(items[1].kind(), domain::ItemKind::PR);
+        assert_eq!(items[1].state, domain::State::Merged);
+        assert_eq!(items[1].closed_by.as_ref().unwrap().name, "merger");
+        assert_eq!(items[1].by.name, "dependabot");
+        assert_eq!(items[1].by.kind, domain::AuthorKind::Bot);
+        assert_eq!(items[1].labels[0].name, "dependencies");
+        assert_eq!(items[1].labels[0].color.as_deref(), Some("0366d6"));
+        assert!(items[1].labels_truncated);
+        let reviews = items[1].details.reviews().unwrap();
+        assert_eq!(reviews.decision, Some(domain::ReviewDecision::Approved));
+        assert_eq!(reviews.requested, vec!["reviewers", "octocat"]);
+        assert_eq!(reviews.latest[0].by, "hubot");
+        assert_eq!(reviews.latest[0].state, domain::ReviewState::Approved);
+        assert_eq!(
+            items[1].details.checks(),
+            Some(&domain::Checks {
+                status: Some(domain::CiStatus::Failing),
+                failing: vec!["build".to_string(), "ci/deploy".to_string()],
+            })
+        );
+        assert_eq!(
+            items[2].details,
+            domain::ItemDetails::PR {
+                reviews: domain::Reviews::default(),
+                checks: domain::Checks::default(),
+            }
+        );
+        assert!(!items[2].labels_truncated);
+        assert_eq!(
+            items[1].closed_at.unwrap().to_rfc3339(),
+            "2020-02-02T10:00:00+00:00"
+        );
+        assert_eq!(items[2].state, domain::State::Draft);
+        assert!(items[2].closed_at.is_none());
+        assert_eq!(fake.requests()[0].body["variables"]["ids"], json!(ids));
+        assert_eq!(fake.requests()[0].body["variables"]["labels"], json!(1));
+        assert_eq!(
+            fake.requests()[0].body["variables"]["events"],
+            json!(DEFAULT_TIMELINE_EVENTS)
+        );
+    }
+
+    #[test]
+    fn looks_up_the_current_names_of_repos() {
+        let (api_url, fake) = fake_github(vec![reply(
+            200,
+            json!({ "data": { "nodes": [
+                { "__typename": "Repository", "id": "repo-1", "nameWithOwner": "new-owner/bar" },
+                null,
+            ] } }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+
+        let ids = vec!["repo-1".to_string(), "deleted".to_string()];
+        let repos = task::block_on(client.refresh_repos(&ids)).unwrap();
+
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].foreign_id, "repo-1");
+        assert_eq!(repos[0].name.to_string(), "new-owner/bar");
+        assert_eq!(fake.requests()[0].body["variables"]["ids"], json!(ids));
+    }
+
+    #[test]
+    fn keeps_looking_up_repos_after_a_chunk_fails() {
+        let (api_url, fake) = fake_github(vec![
+            reply(400, json!({ "message": "Bad Request" })),
+            reply(
+                200,
+                json!({ "data": { "nodes": [
+                    { "__typename": "Repository", "id": "repo-101", "nameWithOwner": "foo/bar" }
+                ] } }),
+            ),
+        ]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let ids = (1..=101)
+            .map(|nr| format!("repo-{}", nr))
+            .collect::<Vec<_>>();
+
+        let repos = task::block_on(client.refresh_repos(&ids)).unwrap();
+
+        assert_eq!(fake.requests().len(), 2);
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].foreign_id, "repo-101");
+    }
+
+    #[test]
+    fn looks_up_the_other_repos_when_one_is_not_found() {
+        let (api_url, _fake) = fake_github(vec![reply(
+            200,
+            json!({
+                "data": { "nodes": [
+                    null,
+                    { "__typename": "Repository", "id": "repo-2", "nameWithOwner": "foo/baz" },
+                ] },
+                "errors": [missing_node(0, "now-private")]
+            }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+
+        let ids = vec!["now-private".to_string(), "repo-2".to_string()];
+        let repos = task::block_on(client.refresh_repos(&ids)).unwrap();
+
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].name.to_string(), "foo/baz");
+    }
+
+    #[test]
+    fn fetches_the_latest_timeline_events_of_an_item() {
+        let long_comment = "word ".repeat(100);
+        let (api_url, fake) = fake_github(vec![reply(
+            200,
+            json!({ "data": { "repository": { "issueOrPullRequest": {
+                "__typename": "PullRequest",
+                "timelineItems": { "nodes": [
+                    {
+                        "__typename": "IssueComment",
+                        "author": { "__typename": "User", "login": "octocat" },
+                        "bodyText": long_comment,
+                        "publishedAt": "2020-03-01T10:00:00Z",
+                        "createdAt": "2020-03-01T09:59:00Z"
+                    },
+                    {
+                        "__typename": "CrossReferencedEvent",
+                        "actor": { "__typename": "User", "login": "hubot" },
+                        "createdAt": "2020-03-01T11:00:00Z",
+                        "source": {
+                            "__typename": "Issue",
+                            "number": 7,
+                            "title": "Flaky build",
+                            "repository": { "nameWithOwner": "foo/other" }
+                        }
+                    },
+                    {
+                        "__typename": "ClosedEvent",
+                        "actor": null,
+                        "createdAt": "2020-03-01T12:00:00Z"
+                    }
+                ] }
+            } } } }),
+        )]);
+
+        let client = GithubClient::new("some-token")
+            .with_api_url(api_url)
+            .with_timeline_events(3);
+
+        let repo = domain::RepoName::from("foo/bar").unwrap();
+        let timeline = task::block_on(client.timeline(&repo, 12)).unwrap();
+
+        let kinds = timeline.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                domain::TimelineEventKind::Commented,
+                domain::TimelineEventKind::CrossReferenced,
+                domain::TimelineEventKind::Closed,
+            ]
+        );
+
+        let excerpt = timeline[0].detail.as_ref().unwrap();
+        assert!(excerpt.ends_with('…'));
+        assert!(excerpt.chars().count() <= EXCERPT_LENGTH + 1);
+        assert_eq!(timeline[0].by, "octocat");
+
+        assert_eq!(
+            timeline[1].detail.as_deref(),
+            Some("foo/other#7: Flaky build")
+        );
+        assert_eq!(timeline[2].by, "ghost");
+
+        let variables = &fake.requests()[0].body["variables"];
+        assert_eq!(variables["nr"], json!(12));
+        assert_eq!(variables["events"], json!(3));
+    }
+
+    #[test]
+    fn fetches_the_last_commits_of_many_repos_at_once() {
+        let (api_url, fake) = fake_github(vec![reply(
+            200,
+            json!({ "data": { "nodes": [
+                { "__typename": "Repository", "id": "repo-1", "defaultBranchRef": {
+                    "name": "main",
+                    "target": {
+                        "__typename": "Commit",
+                        "oid": "8a2f1e0",
+                        "messageHeadline": "Speed up the build",
+                        "committedDate": "2020-03-03T08:00:00Z",
+                        "author": { "name": "Octo Cat", "user": null },
+                        "statusCheckRollup": { "state": "ERROR" }
+                    }
+                } },
+                { "__typename": "Repository", "id": "repo-2", "defaultBranchRef": null },
+                null
+            ] } }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let ids = vec![
+            "repo-1".to_string(),
+            "repo-2".to_string(),
+            "gone".to_string(),
+        ];
+
+        let commits = task::block_on(client.last_commits(&ids)).unwrap();
+
+        assert_eq!(fake.requests().len(), 1);
+        assert_eq!(fake.requests()[0].body["variables"]["ids"], json!(ids));
+        assert_eq!(commits.len(), 1);
+        let commit = &commits["repo-1"];
+        assert_eq!(commit.branch, "main");
+        assert_eq!(commit.sha, "8a2f1e0");
+        assert_eq!(commit.headline, "Speed up the build");
+        assert_eq!(commit.author, "Octo Cat");
+        assert_eq!(commit.ci_status, Some(domain::CiStatus::Failing));
+    }
+
+    #[test]
+    fn fetches_the_other_last_commits_when_one_repo_is_not_found() {
+        let (api_url, _fake) = fake_github(vec![reply(
+            200,
+            json!({
+                "data": { "nodes": [
+                    null,
+                    { "__typename": "Repository", "id": "repo-2", "defaultBranchRef": {
+                        "name": "main",
+                        "target": {
+                            "__typename": "Commit",
+                            "oid": "3c9d7b1",
+                            "messageHeadline": "Fix the docs",
+                            "committedDate": "2020-03-03T08:00:00Z",
+                            "author": { "name": "Octo Cat", "user": { "login": "octocat" } },
+                            "statusCheckRollup": null
+                        }
+                    } }
+                ] },
+                "errors": [missing_node(0, "gone")]
+            }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let ids = vec!["gone".to_string(), "repo-2".to_string()];
+
+        let commits = task::block_on(client.last_commits(&ids)).unwrap();
+
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits["repo-2"].sha, "3c9d7b1");
+    }
+
+    #[test]
+    fn fetches_the_latest_published_releases() {
+        let (api_url, _fake) = fake_github(vec![reply(
+            200,
+            json!({ "data": { "nodes": [{ "__typename": "Repository", "id": "repo-1", "releases": { "nodes": [
+                {
+                    "id": "MDc6UmVsZWFzZTU=",
+                    "databaseId": null,
+                    "name": null,
+                    "tagName": "v2.2.0",
+                    "url": "https://github.com/foo/bar/releases/tag/v2.2.0",
+                    "isDraft": false,
+                    "isPrerelease": false,
+                    "publishedAt": "2020-03-05T10:00:00Z",
+                    "updatedAt": "2020-03-05T10:00:00Z",
+                    "author": null
+                },
+                {
+                    "id": "MDc6UmVsZWFzZTQ=",
+                    "databaseId": 4_294_967_296i64,
+                    "name": null,
+                    "tagName": "v2.1.1",
+                    "url": "https://github.com/foo/bar/releases/tag/v2.1.1",
+                    "isDraft": false,
+                    "isPrerelease": false,
+                    "publishedAt": "2020-03-05T09:00:00Z",
+                    "updatedAt": "2020-03-05T09:00:00Z",
+                    "author": null
+                },
+                {
+                    "id": "MDc6UmVsZWFzZTM=",
+                    "databaseId": 3,
+                    "name": null,
+                    "tagName": "v2.1.0",
+                    "url": "https://github.com/foo/bar/releases/tag/v2.1.0",
+                    "isDraft": true,
+                    "isPrerelease": false,
+                    "publishedAt": null,
+                    "updatedAt": "2020-03-04T10:00:00Z",
+                    "author": null
+                },
+                {
+                    "id": "MDc6UmVsZWFzZTI=",
+                    "databaseId": 2,
+                    "name": null,
+                    "tagName": "v2.0.0-rc.1",
+                    "url": "https://github.com/foo/bar/releases/tag/v2.0.0-rc.1",
+                    "isDraft": false,
+                    "isPrerelease": true,
+                    "publishedAt": "2020-03-03T10:00:00Z",
+                    "updatedAt": "2020-03-03T10:00:00Z",
+                    "author": {
+                        "login": "octocat",
+                        "url": "https://github.com/octocat",
+                        "avatarUrl": "https://avatars.example.com/octocat"
+                    }
+                },
+                {
+                    "id": "MDc6UmVsZWFzZTE=",
+                    "databaseId": 1,
+                    "name": "The first one",
+                    "tagName": "v1.0.0",
+                    "url": "https://github.com/foo/bar/releases/tag/v1.0.0",
+                    "isDraft": false,
+                    "isPrerelease": false,
+                    "publishedAt": "2020-02-01T10:00:00Z",
+                    "updatedAt": "2020-02-01T10:00:00Z",
+                    "author": null
+                }
+            ] } }] } }),
+        )]);
+
+        let client = GithubClient::new("some-token")
+            .with_api_url(api_url)
+            .with_max_releases(5);
+
+        let mut by_repo = task::block_on(client.releases(&["repo-1".to_string()])).unwrap();
+        let releases = by_repo.remove("repo-1").unwrap();
+
+        // Releases without a usable ID are skipped rather than numbered 0 or truncated
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].kind(), domain::ItemKind::Release);
+        assert_eq!(releases[0].number, 2);
+        assert_eq!(releases[0].title, "v2.0.0-rc.1");
+        assert_eq!(releases[0].by.name, "octocat");
+        let release = releases[0].details.release().unwrap();
+        assert_eq!(release.tag, "v2.0.0-rc.1");
+        assert!(release.prerelease);
+        assert_eq!(releases[1].title, "The first one");
+        assert_eq!(releases[1].by.name, "ghost");
+    }
+
+    #[test]
+    fn fetches_the_releases_of_the_other_repos_when_one_is_not_found() {
+        let (api_url, _fake) = fake_github(vec![reply(
+            200,
+            json!({
+                "data": { "nodes": [
+                    { "__typename": "Repository", "id": "repo-1", "releases": { "nodes": [{
+                        "id": "MDc6UmVsZWFzZTE=",
+                        "databaseId": 1,
+                        "name": null,
+                        "tagName": "v1.0.0",
+                        "url": "https://github.com/foo/bar/releases/tag/v1.0.0",
+                        "isDraft": false,
+                        "isPrerelease": false,
+                        "publishedAt": "2020-02-01T10:00:00Z",
+                        "updatedAt": "2020-02-01T10:00:00Z",
+                        "author": null
+                    }] } },
+                    null
+                ] },
+                "errors": [missing_node(1, "gone")]
+            }),
+        )]);
+
+        let client = GithubClient::new("some-token").with_api_url(api_url);
+        let ids = vec!["repo-1".to_string(), "gone".to_string()];
+
+        let by_repo = task::block_on(client.releases(&ids)).unwrap();
 
-        assert_eq!(entire_repo.len(), 11);
+        assert_eq!(by_repo.len(), 1);
+        assert_eq!(by_repo["repo-1"][0].title, "v1.0.0");
     }
 }
@@ -18,7 +18,10 @@ use tide_naive_static_files::StaticFilesEndpoint;
 
 use db::Db;
 use domain::api::{AddNewRepo, AddTrackedItemsForRepo};
-use domain::ClientForRepositories;
+use domain::{
+    ClientError, ClientForRepositories, InvalidRepoName, ItemKind, ReleaseNotUntrackable,
+    RepoAlreadyExists, RepoNotFound,
+};
 use femme;
 use github::GithubClient;
 
@@ -31,6 +34,7 @@ mod github;
 struct State {
     db: Arc<dyn Db>,
     github: Arc<dyn ClientForRepositories>,
+    untracked: domain::updater::Untracked,
 }
 
 impl State {
@@ -41,6 +45,10 @@ impl State {
     fn client(&self) -> Arc<dyn ClientForRepositories> {
         self.github.clone()
     }
+
+    fn untracked(&self) -> &domain::updater::Untracked {
+        &self.untracked
+    }
 }
 
 fn from_url(val: String) -> String {
@@ -91,11 +99,55 @@ fn main() -> anyhow::Result<()> {
     let db_access = config.database.get().unwrap();
 
     // let db_access = Arc::new(crate::db::in_memory::new());
-    let github_access = Arc::new(GithubClient::new(config.github.token.clone()));
+    let mut github_client = GithubClient::new(config.github.token.clone())
+        .with_api_url(
+            config
+                .github
+                .api_url
+                .clone()
+                .unwrap_or_else(|| github::DEFAULT_API_URL.to_string()),
+        )
+        .with_max_items(config.github.max_items.unwrap_or(github::DEFAULT_MAX_ITEMS))
+        .with_max_labels(
+            config
+                .github
+                .max_labels
+                .unwrap_or(github::DEFAULT_MAX_LABELS),
+        )
+        .with_timeline_events(
+            config
+                .github
+                .timeline_events
+                .unwrap_or(github::DEFAULT_TIMELINE_EVENTS),
+        )
+        .with_max_releases(
+            config
+                .github
+                .max_releases
+                .unwrap_or(github::DEFAULT_MAX_RELEASES),
+        )
+        .with_backoff(github::Backoff {
+            max_retries: config
+                .github
+                .max_retries
+                .unwrap_or(github::Backoff::default().max_retries),
+            ..github::Backoff::default()
+        });
+
+    for (name, value) in config.github.headers.iter() {
+        github_client = github_client
+            .with_header(name, value)
+            .with_context(|| "invalid header in the github config")?;
+    }
+
+    let github_access = Arc::new(github_client);
+
+    let untracked = domain::updater::Untracked::default();
 
     let state = State {
         db: db_access.clone(),
         github: github_access.clone(),
+        untracked: untracked.clone(),
     };
 
     let mut app = tide::with_state(state.clone());
@@ -130,7 +182,9 @@ fn main() -> anyhow::Result<()> {
             let AddNewRepo { name } = req.body_json().await.unwrap();
 
             ApiResult::empty(
-                domain::add_new_repo(db, client, name).with_context(|| "failed to add repo"),
+                domain::add_new_repo(db, client, name)
+                    .await
+                    .with_context(|| "failed to add repo"),
             )
         });
     api_routes
@@ -149,12 +203,77 @@ fn main() -> anyhow::Result<()> {
 
             let repo = maybe_repo.unwrap();
 
-            ApiResult::empty(
-                domain::add_items_to_track(db, client, repo, items)
+            ApiResult::from(
+                domain::add_items_to_track(db, client, req.state().untracked(), repo, items)
                     .await
                     .with_context(|| "failed to add items to track"),
             )
         });
+    api_routes
+        .at("/repos/:name/tracked/:kind/:nr")
+        .delete(|req: Request<State>| async move {
+            let name = from_url(req.param("name").unwrap());
+            let db = req.state().db();
+
+            let kind = req.param::<ItemKind>("kind");
+            let nr = req.param::<i32>("nr");
+            let maybe_repo = db.find_repo(&name);
+
+            if kind.is_err() || nr.is_err() || maybe_repo.is_none() {
+                return ApiResult::<()>::not_found();
+            }
+
+            let repo = maybe_repo.unwrap();
+
+            match domain::untrack_item(
+                db,
+                req.state().untracked(),
+                repo,
+                kind.unwrap(),
+                nr.unwrap(),
+            ) {
+                Ok(true) => ApiResult::Empty,
+                Ok(false) => ApiResult::not_found(),
+                Err(e) => ApiResult::Failure(ApiError::from(
+                    e.context("failed to stop tracking the item"),
+                )),
+            }
+        });
+    api_routes
+        .at("/repos/:name/items/:nr/history")
+        .get(|req: Request<State>| async move {
+            let name = from_url(req.param("name").unwrap());
+            let db = req.state().db();
+
+            let nr = req.param::<i32>("nr");
+            let maybe_repo = db.find_repo(&name);
+
+            if nr.is_err() || maybe_repo.is_none() {
+                return ApiResult::not_found();
+            }
+
+            match domain::item_history(db, maybe_repo.unwrap(), nr.unwrap()) {
+                Ok(Some(history)) => ApiResult::Success(history),
+                Ok(None) => ApiResult::not_found(),
+                Err(e) => ApiResult::Failure(ApiError::from(
+                    e.context("failed to load the history of the item"),
+                )),
+            }
+        });
+    api_routes
+        .at("/repos/:name/history")
+        .get(|req: Request<State>| async move {
+            let name = from_url(req.param("name").unwrap());
+            let db = req.state().db();
+
+            match db.find_repo(&name) {
+                Some(repo) => ApiResult::from(
+                    domain::repo_history(db, repo)
+                        .with_context(|| "failed to load the history of the repo"),
+                ),
+                None => ApiResult::not_found(),
+            }
+        });
     api_routes
         .at("/repos/:name/proxy")
         .get(|req: Request<State>| async move {
@@ -176,6 +295,12 @@ fn main() -> anyhow::Result<()> {
                     .with_context(|| "failed to add items to track"),
             )
         });
+    api_routes
+        .at("/status/rate-limit")
+        .get(|req: Request<State>| async move {
+            let client = req.state().client();
+            ApiResult::Success(domain::current_rate_limit(client))
+        });
     api_routes
         .at("/repos/:name")
         .delete(|req: Request<State>| async move {
@@ -196,17 +321,11 @@ fn main() -> anyhow::Result<()> {
 
     if config.updater.run {
         let db = db_access.clone();
-        let (sender, receiver) = async_std::sync::channel(100);
+        let (sender, receiver) = async_std::sync::channel(1);
         task::spawn(async move {
             let mut interval = stream::interval(Duration::from_secs(30));
             while let Some(_) = interval.next().await {
-                let all_repos = db.all().unwrap();
-
-                for repo in all_repos {
-                    for item in repo.items() {
-                        sender.send((repo.stored(), item)).await;
-                    }
-                }
+                sender.send(db.all().unwrap()).await;
             }
         });
 
@@ -215,6 +334,17 @@ fn main() -> anyhow::Result<()> {
             channel: receiver,
             client: github,
             db: db_access.clone(),
+            rate_limit_reserve: config
+                .updater
+                .rate_limit_reserve
+                .unwrap_or(domain::updater::DEFAULT_RATE_LIMIT_RESERVE),
+            keep_resolved: chrono::Duration::hours(i64::from(
+                config
+                    .updater
+                    .keep_resolved_hours
+                    .unwrap_or(domain::updater::DEFAULT_KEEP_RESOLVED_HOURS),
+            )),
+            untracked,
         });
     }
 
@@ -244,10 +374,7 @@ impl<T> std::convert::From<anyhow::Result<T>> for ApiResult<T> {
 
         match res {
             Ok(val) => Success(val),
-            Err(e) => Failure(ApiError {
-                status: 500,
-                error: e,
-            }),
+            Err(e) => Failure(ApiError::from(e)),
         }
     }
 }
@@ -271,10 +398,7 @@ impl<T> ApiResult<T> {
 
         match result {
             Ok(_) => Empty,
-            Err(e) => Failure(ApiError {
-                status: 500,
-                error: e,
-            }),
+            Err(e) => Failure(ApiError::from(e)),
         }
     }
 }
@@ -284,6 +408,23 @@ struct ApiError {
     error: anyhow::Error,
 }
 
+impl std::convert::From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> ApiError {
+        let status = match error.downcast_ref::<ClientError>() {
+            Some(ClientError::Transient(_)) => 503,
+            Some(ClientError::NotFound(_)) => 404,
+            Some(ClientError::Permanent(_)) => 502,
+            None if error.is::<InvalidRepoName>() => 400,
+            None if error.is::<ReleaseNotUntrackable>() => 400,
+            None if error.is::<RepoNotFound>() => 404,
+            None if error.is::<RepoAlreadyExists>() => 409,
+            None => 500,
+        };
+
+        ApiError { status, error }
+    }
+}
+
 #[derive(Serialize)]
 struct ErrorJson {
     error: String,
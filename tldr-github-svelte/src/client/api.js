@@ -38,6 +38,14 @@ export const trackItems = async (name, items) => {
     }
 };
 
+export const untrackItem = async (name, kind, nr) => {
+    try {
+        return await doDelete(`/repos/${to_url(name)}/tracked/${kind}/${nr}`)
+    } catch (e) {
+        newError(`Unable to stop tracking ${kind} ${nr} of ${name}: ${e}`)
+    }
+};
+
 const doPost = async (path, data) => {
     return fetch(`/api${path}`, {
         "body": JSON.stringify(data),
@@ -1,14 +1,14 @@
 export const search = (items, search, fields) => {
     return items.filter(item => {
         for (const field of fields) {
-            const value = item[field];
+            const value = field.split(".").reduce((v, key) => v && v[key], item);
 
             if (!value) {
                 continue
             }
 
             if (Array.isArray(value)) {
-                if (value.some((v) => search.test(v))) {
+                if (value.some((v) => search.test(v.name || v))) {
                     return true
                 }
             }